@@ -10,19 +10,25 @@ use wasm_bindgen::{
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{
     CanvasRenderingContext2d, Document, Element, HtmlCanvasElement, HtmlElement, HtmlImageElement,
-    Window,
+    Storage, UrlSearchParams, WebGl2RenderingContext, Window,
 };
 
 macro_rules! log {
-    ($($t:tt)*) => {
+    ($($t:tt)*) => {{
+        #[cfg(target_arch = "wasm32")]
         web_sys::console::log_1(&format!( $( $t )* ).into());
-    };
+        #[cfg(not(target_arch = "wasm32"))]
+        println!( $( $t )* );
+    }};
 }
 
 macro_rules! error {
-    ($($t:tt)*) => {
+    ($($t:tt)*) => {{
+        #[cfg(target_arch = "wasm32")]
         web_sys::console::error_1(&format!( $( $t )* ).into());
-    };
+        #[cfg(not(target_arch = "wasm32"))]
+        eprintln!( $( $t )* );
+    }};
 }
 
 pub fn window() -> Result<Window> {
@@ -43,6 +49,15 @@ pub fn canvas() -> Result<HtmlCanvasElement> {
         .map_err(|element| anyhow!("Error converting {:#?} to HtmlCanvasElement", element))
 }
 
+pub fn canvas_size() -> Result<(f64, f64)> {
+    let canvas = canvas()?;
+    Ok((canvas.client_width().into(), canvas.client_height().into()))
+}
+
+pub fn device_pixel_ratio() -> Result<f64> {
+    Ok(window()?.device_pixel_ratio())
+}
+
 pub fn context() -> Result<CanvasRenderingContext2d> {
     canvas()?
         .get_context("2d")
@@ -57,6 +72,28 @@ pub fn context() -> Result<CanvasRenderingContext2d> {
         })
 }
 
+pub fn webgl2_context() -> Result<WebGl2RenderingContext> {
+    canvas()?
+        .get_context("webgl2")
+        .map_err(|js_value| anyhow!("Error getting webgl2 context {:#?}", js_value))?
+        .ok_or_else(|| anyhow!("No webgl2 context found"))?
+        .dyn_into::<WebGl2RenderingContext>()
+        .map_err(|element| anyhow!("Error converting {:#?} to WebGl2RenderingContext", element))
+}
+
+/// A canvas keeps the first kind of context it hands out, so this swaps in a copy.
+pub fn replace_canvas() -> Result<()> {
+    let canvas = canvas()?;
+    let fresh = canvas
+        .clone_node()
+        .map_err(|err| anyhow!("Could not copy the canvas {:#?}", err))?
+        .dyn_into::<HtmlCanvasElement>()
+        .map_err(|element| anyhow!("Error converting {:#?} to HtmlCanvasElement", element))?;
+    canvas
+        .replace_with_with_node_1(&fresh)
+        .map_err(|err| anyhow!("Could not replace the canvas {:#?}", err))
+}
+
 pub fn spawn_local<F>(future: F)
 where
     F: Future<Output = ()> + 'static,
@@ -134,6 +171,24 @@ pub fn now() -> Result<f64> {
         .now())
 }
 
+pub fn query_param(name: &str) -> Result<Option<String>> {
+    let search = window()?
+        .location()
+        .search()
+        .map_err(|e| anyhow!("Could not read location search {:#?}", e))?;
+
+    UrlSearchParams::new_with_str(&search)
+        .map(|params| params.get(name))
+        .map_err(|e| anyhow!("Could not parse query string {:#?}", e))
+}
+
+pub fn local_storage() -> Result<Storage> {
+    window()?
+        .local_storage()
+        .map_err(|e| anyhow!("Could not get local storage {:#?}", e))?
+        .ok_or_else(|| anyhow!("No local storage found"))
+}
+
 pub fn draw_ui(html: &str) -> Result<()> {
     find_ui()?
         .insert_adjacent_html("afterbegin", html)
@@ -177,7 +232,7 @@ pub fn find_html_element_by_id(id: &str) -> Result<HtmlElement> {
         })
 }
 
-#[cfg(test)]
+#[cfg(all(test, target_arch = "wasm32"))]
 mod tests {
     use super::*;
     use wasm_bindgen_test::wasm_bindgen_test;
@@ -188,6 +243,6 @@ mod tests {
     async fn test_error_loading_json() {
         let json = fetch_json("not_there.json").await;
 
-        assert_eq!(json.is_err(), true);
+        assert!(json.is_err());
     }
 }
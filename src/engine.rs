@@ -1,25 +1,49 @@
 pub mod audio;
+pub mod camera;
 pub mod click_event;
+pub mod collision;
+pub mod context_event;
 pub mod game;
 pub mod game_loop;
+pub mod gamepad;
 pub mod image;
 pub mod key_event;
 pub mod load_asset;
+pub mod parallax;
+pub mod platform;
 pub mod point;
 pub mod rect;
 pub mod renderer;
+pub mod replay;
+pub mod resize_event;
 pub mod sheet;
 pub mod sprite_sheet;
+pub mod touch_event;
+pub mod vector;
+pub mod viewport;
+pub mod visibility_event;
 
 pub(crate) use audio::*;
+pub use camera::*;
 pub(crate) use click_event::*;
+pub(crate) use collision::*;
+pub use context_event::*;
 pub use game::*;
 pub use game_loop::*;
+pub use gamepad::*;
 pub(crate) use image::Image;
 pub use key_event::*;
 pub use load_asset::*;
+pub(crate) use parallax::*;
+pub use platform::*;
 pub use point::*;
 pub use rect::*;
 pub use renderer::*;
+pub use replay::*;
+pub(crate) use resize_event::*;
 pub(crate) use sheet::*;
 pub(crate) use sprite_sheet::*;
+pub use touch_event::*;
+pub use vector::*;
+pub use viewport::*;
+pub(crate) use visibility_event::*;
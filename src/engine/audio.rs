@@ -1,38 +1,40 @@
 use anyhow::Result;
-use web_sys::{AudioBuffer, AudioContext};
+use std::rc::Rc;
 
-use crate::{browser, sound};
+use super::{AudioDevice, SoundSource};
 
 #[derive(Clone)]
 pub(crate) struct Audio {
-    context: AudioContext,
+    device: Rc<dyn AudioDevice>,
 }
 
 impl Audio {
-    pub(crate) fn new() -> Result<Self> {
-        Ok(Self {
-            context: sound::create_audio_context()?,
-        })
+    pub(crate) fn new(device: Rc<dyn AudioDevice>) -> Self {
+        Self { device }
     }
 
     pub(crate) async fn load_sound(&self, filename: &str) -> Result<Sound> {
-        let array_buffer = browser::fetch_array_buffer(filename).await?;
-        let audio_buffer = sound::decode_audio_data(&self.context, &array_buffer).await?;
-        Ok(Sound {
-            buffer: audio_buffer,
-        })
+        self.device.load_sound(filename).await
     }
 
     pub(crate) fn play_sound(&self, sound: &Sound) -> Result<()> {
-        sound::play_sound(&self.context, &sound.buffer, sound::Looping::No)
+        self.device.play_sound(sound)
     }
 
     pub(crate) fn play_looping_sound(&self, sound: &Sound) -> Result<()> {
-        sound::play_sound(&self.context, &sound.buffer, sound::Looping::Yes)
+        self.device.play_looping_sound(sound)
+    }
+
+    pub(crate) fn suspend(&self) -> Result<()> {
+        self.device.suspend()
+    }
+
+    pub(crate) fn resume(&self) -> Result<()> {
+        self.device.resume()
     }
 }
 
 #[derive(Clone)]
 pub struct Sound {
-    pub buffer: AudioBuffer,
+    pub buffer: Rc<dyn SoundSource>,
 }
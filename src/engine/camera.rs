@@ -0,0 +1,104 @@
+use super::{Point, Rect, Vector};
+
+/// Follows a target with easing and can shake.
+pub struct Camera {
+    position: Vector,
+    width: i16,
+    height: i16,
+    shake_ticks: u8,
+    shake_duration: u8,
+    shake_magnitude: i16,
+}
+
+impl Camera {
+    pub fn new(width: i16, height: i16) -> Self {
+        Camera {
+            position: Vector::default(),
+            width,
+            height,
+            shake_ticks: 0,
+            shake_duration: 0,
+            shake_magnitude: 0,
+        }
+    }
+
+    pub fn x(&self) -> f32 {
+        self.position.x
+    }
+
+    pub fn follow(&mut self, x: f32, easing: f32) {
+        let target = Vector {
+            x,
+            y: self.position.y,
+        };
+        self.position += (target - self.position) * easing;
+    }
+
+    pub fn shake(&mut self, ticks: u8, magnitude: i16) {
+        self.shake_ticks = ticks;
+        self.shake_duration = ticks;
+        self.shake_magnitude = magnitude;
+    }
+
+    pub fn update(&mut self) {
+        self.shake_ticks = self.shake_ticks.saturating_sub(1);
+    }
+
+    pub fn move_horizontally(&mut self, x: i16) {
+        self.position.x += f32::from(x);
+    }
+
+    pub fn offset(&self) -> Point {
+        let mut offset = self.position.round();
+        if self.shake_ticks > 0 {
+            let amount =
+                self.shake_magnitude * i16::from(self.shake_ticks) / i16::from(self.shake_duration);
+            let direction = if self.shake_ticks.is_multiple_of(2) {
+                1
+            } else {
+                -1
+            };
+            offset.x += amount * direction;
+            offset.y += amount * -direction / 2;
+        }
+        offset
+    }
+
+    pub fn visible(&self) -> Rect {
+        Rect::new(self.position.round(), self.width, self.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn following_eases_towards_the_target() {
+        let mut camera = Camera::new(600, 600);
+
+        camera.follow(100.0, 0.5);
+        assert_eq!(camera.visible().x(), 50);
+        camera.follow(100.0, 0.5);
+        assert_eq!(camera.visible().x(), 75);
+        assert_eq!(camera.visible().right(), 675);
+    }
+
+    #[test]
+    fn shaking_moves_the_view_back_and_forth_then_settles() {
+        let mut camera = Camera::new(600, 600);
+        camera.shake(4, 8);
+
+        let offsets: Vec<i16> = (0..5)
+            .map(|_| {
+                let x = camera.offset().x;
+                camera.update();
+                x
+            })
+            .collect();
+
+        assert_eq!(offsets, vec![8, -6, 4, -2, 0]);
+        assert_eq!(camera.offset(), Point { x: 0, y: 0 });
+        assert_eq!(camera.visible().x(), 0);
+    }
+}
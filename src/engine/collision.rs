@@ -0,0 +1,345 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::{load_json, Platform, Point, Rect};
+
+/// In frame coordinates. Polygons must be convex.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum Shape {
+    Rect { x: i16, y: i16, w: i16, h: i16 },
+    Polygon { points: Vec<[i16; 2]> },
+}
+
+impl Shape {
+    /// Shapes that only touch don't overlap.
+    pub(crate) fn overlaps(&self, origin: &Point, other: &Shape, other_origin: &Point) -> bool {
+        let a = self.vertices(origin);
+        let b = other.vertices(other_origin);
+
+        !has_separating_axis(&a, &a, &b) && !has_separating_axis(&b, &a, &b)
+    }
+
+    pub(crate) fn bounds(&self, origin: &Point) -> Rect {
+        let vertices = self.vertices(origin);
+        let left = vertices
+            .iter()
+            .map(|point| point.x)
+            .min()
+            .unwrap_or(origin.x);
+        let right = vertices
+            .iter()
+            .map(|point| point.x)
+            .max()
+            .unwrap_or(origin.x);
+        let top = vertices
+            .iter()
+            .map(|point| point.y)
+            .min()
+            .unwrap_or(origin.y);
+        let bottom = vertices
+            .iter()
+            .map(|point| point.y)
+            .max()
+            .unwrap_or(origin.y);
+        Rect::new_from_x_y(left, top, right - left, bottom - top)
+    }
+
+    fn validate(&self) -> Result<()> {
+        match self {
+            Shape::Rect { w, h, .. } if *w <= 0 || *h <= 0 => {
+                Err(anyhow!("Collision rect of {}x{} has no area", w, h))
+            }
+            Shape::Polygon { points } if points.len() < 3 => Err(anyhow!(
+                "Collision polygon needs at least 3 points, not {}",
+                points.len()
+            )),
+            Shape::Polygon { points } if !is_convex(points) => {
+                Err(anyhow!("Collision polygon {:?} is not convex", points))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn vertices(&self, origin: &Point) -> Vec<Point> {
+        let corner = |x: i16, y: i16| Point {
+            x: origin.x + x,
+            y: origin.y + y,
+        };
+        match self {
+            Shape::Rect { x, y, w, h } => vec![
+                corner(*x, *y),
+                corner(x + w, *y),
+                corner(x + w, y + h),
+                corner(*x, y + h),
+            ],
+            Shape::Polygon { points } => points.iter().map(|[x, y]| corner(*x, *y)).collect(),
+        }
+    }
+}
+
+impl From<&Rect> for Shape {
+    fn from(rect: &Rect) -> Self {
+        Shape::Rect {
+            x: rect.x(),
+            y: rect.y(),
+            w: rect.width,
+            h: rect.height,
+        }
+    }
+}
+
+pub(crate) struct PlacedShapes<'a> {
+    pub(crate) shapes: &'a [Shape],
+    pub(crate) origin: Point,
+}
+
+impl PlacedShapes<'_> {
+    pub(crate) fn overlaps(&self, other: &PlacedShapes) -> bool {
+        self.shapes.iter().any(|shape| {
+            other
+                .shapes
+                .iter()
+                .any(|other_shape| shape.overlaps(&self.origin, other_shape, &other.origin))
+        })
+    }
+}
+
+/// Keyed like `Sheet::frames`.
+#[derive(Deserialize, Clone, Default)]
+pub(crate) struct CollisionShapes {
+    frames: HashMap<String, Vec<Shape>>,
+}
+
+impl CollisionShapes {
+    pub(crate) async fn load(platform: &dyn Platform, json_path: &str) -> Result<Self> {
+        let shapes = load_json::<CollisionShapes>(platform, json_path).await?;
+        shapes.validate()?;
+        Ok(shapes)
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        for (frame, shapes) in &self.frames {
+            for shape in shapes {
+                shape
+                    .validate()
+                    .map_err(|err| anyhow!("Bad collision shape for {}: {}", frame, err))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn shapes(&self, frame: &str) -> Option<&[Shape]> {
+        self.frames.get(frame).map(Vec::as_slice)
+    }
+}
+
+/// Every corner must turn the same way, and all of them together once around,
+/// which also rules out collinear points and self-intersecting outlines.
+fn is_convex(points: &[[i16; 2]]) -> bool {
+    let edge = |i: usize| {
+        let [x0, y0] = points[i];
+        let [x1, y1] = points[(i + 1) % points.len()];
+        (f64::from(x1 - x0), f64::from(y1 - y0))
+    };
+    let turns: Vec<f64> = (0..points.len())
+        .map(|i| {
+            let (ax, ay) = edge(i);
+            let (bx, by) = edge((i + 1) % points.len());
+            (ax * by - ay * bx).atan2(ax * bx + ay * by)
+        })
+        .collect();
+
+    let total: f64 = turns.iter().sum();
+    (turns.iter().all(|turn| *turn > 0.0) || turns.iter().all(|turn| *turn < 0.0))
+        && (total.abs() - std::f64::consts::TAU).abs() < 1e-6
+}
+
+fn has_separating_axis(edges: &[Point], a: &[Point], b: &[Point]) -> bool {
+    (0..edges.len()).any(|i| {
+        let start = edges[i];
+        let end = edges[(i + 1) % edges.len()];
+        let axis = (
+            -(i32::from(end.y) - i32::from(start.y)),
+            i32::from(end.x) - i32::from(start.x),
+        );
+        let (min_a, max_a) = project(a, axis);
+        let (min_b, max_b) = project(b, axis);
+        max_a <= min_b || max_b <= min_a
+    })
+}
+
+fn project(points: &[Point], axis: (i32, i32)) -> (i32, i32) {
+    points
+        .iter()
+        .map(|point| i32::from(point.x) * axis.0 + i32::from(point.y) * axis.1)
+        .fold((i32::MAX, i32::MIN), |(min, max), dot| {
+            (min.min(dot), max.max(dot))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::NativePlatform;
+    use futures::executor::block_on;
+
+    const ORIGIN: Point = Point { x: 100, y: 100 };
+
+    fn triangle() -> Shape {
+        Shape::Polygon {
+            points: vec![[0, 0], [40, 0], [0, 40]],
+        }
+    }
+
+    fn intersects(shape: &Shape, rect: &Rect) -> bool {
+        shape.overlaps(&ORIGIN, &Shape::from(rect), &Point::default())
+    }
+
+    #[test]
+    fn a_rect_inside_the_bounds_but_outside_a_polygon_does_not_collide() {
+        let corner = Rect::new_from_x_y(130, 130, 10, 10);
+
+        assert!(!intersects(&triangle(), &corner));
+    }
+
+    #[test]
+    fn a_rect_overlapping_a_polygon_collides() {
+        let overlapping = Rect::new_from_x_y(110, 110, 10, 10);
+        let touching = Rect::new_from_x_y(140, 90, 10, 10);
+
+        assert!(intersects(&triangle(), &overlapping));
+        assert!(!intersects(&triangle(), &touching));
+    }
+
+    #[test]
+    fn rect_shapes_are_placed_relative_to_the_frame() {
+        let shape = Shape::Rect {
+            x: 10,
+            y: 20,
+            w: 5,
+            h: 5,
+        };
+
+        assert!(intersects(&shape, &Rect::new_from_x_y(112, 122, 1, 1)));
+        assert!(!intersects(&shape, &Rect::new_from_x_y(12, 22, 1, 1)));
+    }
+
+    #[test]
+    fn polygons_whose_bounds_overlap_can_still_miss() {
+        let triangle = [triangle()];
+        let flipped = [Shape::Polygon {
+            points: vec![[40, 0], [40, 40], [0, 40]],
+        }];
+        let near_miss = PlacedShapes {
+            shapes: &flipped,
+            origin: Point { x: 121, y: 121 },
+        };
+        let at = |origin: Point| PlacedShapes {
+            shapes: &triangle,
+            origin,
+        };
+
+        assert!(triangle[0]
+            .bounds(&ORIGIN)
+            .intersects(&flipped[0].bounds(&near_miss.origin)));
+        assert!(!at(ORIGIN).overlaps(&near_miss));
+        assert!(at(Point { x: 130, y: 130 }).overlaps(&near_miss));
+    }
+
+    #[test]
+    fn bounds_surround_the_placed_shape() {
+        assert_eq!(
+            triangle().bounds(&ORIGIN),
+            Rect::new_from_x_y(100, 100, 40, 40)
+        );
+    }
+
+    #[test]
+    fn loads_shapes_by_frame_name() {
+        let shapes: CollisionShapes = serde_json::from_str(
+            r#"{ "frames": { "Run (1).png": [
+                { "kind": "rect", "x": 0, "y": 0, "w": 10, "h": 10 },
+                { "kind": "polygon", "points": [[0, 0], [40, 0], [0, 40]] }
+            ] } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(shapes.shapes("Run (1).png").unwrap()[1], triangle());
+        assert!(shapes.shapes("Run (2).png").is_none());
+        assert!(shapes.validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_polygons_with_fewer_than_three_points() {
+        let shapes: CollisionShapes = serde_json::from_str(
+            r#"{ "frames": { "Run (1).png": [
+                { "kind": "polygon", "points": [[0, 0], [40, 0]] }
+            ] } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            shapes.validate().unwrap_err().to_string(),
+            "Bad collision shape for Run (1).png: Collision polygon needs at least 3 points, not 2"
+        );
+    }
+
+    #[test]
+    fn loading_a_concave_polygon_fails() {
+        let assets = std::env::temp_dir().join(format!("concave_shapes_{}", std::process::id()));
+        std::fs::create_dir_all(&assets).unwrap();
+        std::fs::write(
+            assets.join("shapes.json"),
+            r#"{ "frames": { "Stone.png": [
+                { "kind": "polygon", "points": [[0, 0], [40, 0], [20, 10], [40, 40], [0, 40]] }
+            ] } }"#,
+        )
+        .unwrap();
+
+        let loaded = block_on(CollisionShapes::load(
+            &NativePlatform::new(&assets),
+            "shapes.json",
+        ));
+        std::fs::remove_dir_all(&assets).unwrap();
+
+        assert_eq!(
+            loaded.err().unwrap().to_string(),
+            "Bad collision shape for Stone.png: \
+             Collision polygon [[0, 0], [40, 0], [20, 10], [40, 40], [0, 40]] is not convex"
+        );
+    }
+
+    #[test]
+    fn rejects_concave_collinear_and_self_intersecting_polygons() {
+        let error = |points: &str| {
+            let json = format!(
+                r#"{{ "frames": {{ "Stone.png": [ {{ "kind": "polygon", "points": {} }} ] }} }}"#,
+                points
+            );
+            serde_json::from_str::<CollisionShapes>(&json)
+                .unwrap()
+                .validate()
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert!(error("[[0, 0], [40, 0], [20, 10], [40, 40], [0, 40]]").contains("is not convex"));
+        assert!(error("[[0, 0], [20, 0], [40, 0]]").contains("is not convex"));
+        assert!(error("[[0, 0], [40, 40], [40, 0], [0, 40]]").contains("is not convex"));
+    }
+
+    #[test]
+    fn accepts_convex_polygons_either_way_round() {
+        let shapes: CollisionShapes = serde_json::from_str(
+            r#"{ "frames": { "Stone.png": [
+                { "kind": "polygon", "points": [[0, 0], [40, 0], [40, 40], [0, 40]] },
+                { "kind": "polygon", "points": [[0, 40], [40, 40], [40, 0], [0, 0]] }
+            ] } }"#,
+        )
+        .unwrap();
+
+        assert!(shapes.validate().is_ok());
+    }
+}
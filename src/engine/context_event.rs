@@ -0,0 +1,33 @@
+use crate::browser;
+use anyhow::{anyhow, Result};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+use wasm_bindgen::JsCast;
+use web_sys::Event;
+
+/// Everything uploaded to a lost context is gone once it is restored.
+pub fn add_context_restored_handler() -> Result<UnboundedReceiver<()>> {
+    let canvas = browser::canvas()?;
+    let (mut restored_sender, restored_receiver) = unbounded();
+    // The context is only restored if the loss is cancelled.
+    let on_lost = browser::closure_wrap(Box::new(move |event: Event| {
+        event.prevent_default();
+    }) as Box<dyn FnMut(Event)>);
+    let on_restored = browser::closure_wrap(Box::new(move || {
+        if let Err(err) = restored_sender.start_send(()) {
+            error!("Could not send context restored message {:#?}", err);
+        }
+    }) as Box<dyn FnMut()>);
+    canvas
+        .add_event_listener_with_callback("webglcontextlost", on_lost.as_ref().unchecked_ref())
+        .map_err(|err| anyhow!("Could not listen for context loss {:#?}", err))?;
+    canvas
+        .add_event_listener_with_callback(
+            "webglcontextrestored",
+            on_restored.as_ref().unchecked_ref(),
+        )
+        .map_err(|err| anyhow!("Could not listen for context restores {:#?}", err))?;
+    on_lost.forget();
+    on_restored.forget();
+
+    Ok(restored_receiver)
+}
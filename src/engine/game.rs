@@ -7,5 +7,12 @@ use async_trait::async_trait;
 pub trait Game {
     async fn initialize(&self) -> Result<Box<dyn Game>>;
     fn update(&mut self, keystate: &KeyState);
-    fn draw(&mut self, renderer: &Renderer);
+    fn resolution(&self) -> (i16, i16);
+    /// `alpha` is how far this frame falls between two updates, from 0 to 1.
+    fn draw(&mut self, renderer: &dyn Renderer, alpha: f32);
+
+    /// A paused game gets one update per frame, never a burst of catch-up ones.
+    fn is_paused(&self) -> bool {
+        false
+    }
 }
@@ -2,50 +2,174 @@ use crate::browser::{self, LoopClosure};
 use crate::engine::*;
 
 use anyhow::{anyhow, Result};
+use futures::channel::mpsc::UnboundedReceiver;
 use std::{cell::RefCell, rc::Rc};
 
 const FRAME_SIZE: f32 = 1.0 / 60.0 * 1000.0;
 
+/// Time beyond these limits is dropped instead of being caught up.
+#[derive(Clone, Copy, Debug)]
+pub struct GameLoopConfig {
+    pub max_frame_time: f32,
+    pub max_updates_per_frame: u32,
+    pub renderer: RendererBackend,
+}
+
+impl Default for GameLoopConfig {
+    fn default() -> Self {
+        GameLoopConfig {
+            max_frame_time: 250.0,
+            max_updates_per_frame: 5,
+            renderer: RendererBackend::default(),
+        }
+    }
+}
+
 pub struct GameLoop {
     last_frame: f64,
     accumulated_delta: f32,
+    config: GameLoopConfig,
 }
 
 type SharedLoopClosure = Rc<RefCell<Option<LoopClosure>>>;
 
+enum Input {
+    Live(UnboundedReceiver<KeyPress>),
+    Replay(Replay, UnboundedReceiver<KeyPress>),
+}
+
+impl Input {
+    /// Paused ticks were never recorded, so a replay holds its place.
+    fn next_tick(&mut self, keystate: &mut KeyState, paused: bool) {
+        match self {
+            Input::Live(keyevent_receiver) => {
+                process_input(keystate, keyevent_receiver);
+                poll_gamepads(keystate);
+            }
+            Input::Replay(_, keyevent_receiver) if paused => {
+                process_input(keystate, keyevent_receiver);
+            }
+            Input::Replay(replay, keyevent_receiver) => {
+                while let Ok(Some(_)) = keyevent_receiver.try_next() {}
+                if !replay.next_tick(keystate) {
+                    keystate.update_pressed([]);
+                }
+            }
+        }
+    }
+}
+
 impl GameLoop {
-    pub async fn start(game: impl Game + 'static) -> Result<()> {
-        let mut keyevent_receiver = prepare_input()?;
-        let mut game = game.initialize().await?;
-        let mut game_loop = GameLoop {
-            last_frame: browser::now()?,
+    pub async fn start(
+        game: impl Game + 'static,
+        platform: &dyn Platform,
+        config: GameLoopConfig,
+    ) -> Result<()> {
+        Self::run(game, platform, None, config).await
+    }
+
+    pub async fn replay(
+        game: impl Game + 'static,
+        platform: &dyn Platform,
+        recording: Recording,
+        config: GameLoopConfig,
+    ) -> Result<()> {
+        Self::run(game, platform, Some(Replay::new(recording)), config).await
+    }
+
+    fn new(now: f64, config: GameLoopConfig) -> Self {
+        GameLoop {
+            last_frame: now,
             accumulated_delta: 0.0,
+            config,
+        }
+    }
+
+    fn advance(&mut self, now: f64) -> u32 {
+        let frame_time = ((now - self.last_frame) as f32).min(self.config.max_frame_time);
+        self.last_frame = now;
+        self.accumulated_delta += frame_time.max(0.0);
+
+        let updates =
+            ((self.accumulated_delta / FRAME_SIZE) as u32).min(self.config.max_updates_per_frame);
+        self.accumulated_delta -= updates as f32 * FRAME_SIZE;
+        if updates == self.config.max_updates_per_frame {
+            self.accumulated_delta = self.accumulated_delta.min(FRAME_SIZE);
+        }
+        updates
+    }
+
+    fn pause(&mut self) {
+        self.accumulated_delta = 0.0;
+    }
+
+    fn alpha(&self) -> f32 {
+        (self.accumulated_delta / FRAME_SIZE).clamp(0.0, 1.0)
+    }
+
+    async fn run(
+        game: impl Game + 'static,
+        platform: &dyn Platform,
+        replay: Option<Replay>,
+        config: GameLoopConfig,
+    ) -> Result<()> {
+        // This may replace the canvas, so it comes before any listeners.
+        let mut renderer = create_renderer(config.renderer)?;
+        let mut context_restored_events = add_context_restored_handler()?;
+        let mut input = match replay {
+            Some(replay) => Input::Replay(replay, platform.prepare_input()?),
+            None => Input::Live(platform.prepare_input()?),
         };
-        let renderer = Renderer::new(browser::context()?);
+        let mut game = game.initialize().await?;
+        let mut game_loop = GameLoop::new(browser::now()?, config);
+        let resolution = game.resolution();
+        fit_canvas(renderer.as_ref(), resolution)?;
+        let mut resize_events = add_resize_handler()?;
 
         let f: SharedLoopClosure = Rc::new(RefCell::new(None));
         let g = f.clone();
         let mut keystate = KeyState::new();
 
         *g.borrow_mut() = Some(browser::create_raf_closure(move |perf: f64| {
-            process_input(&mut keystate, &mut keyevent_receiver);
-
             let frame_time = perf - game_loop.last_frame;
-            game_loop.accumulated_delta += frame_time as f32;
 
-            while game_loop.accumulated_delta > FRAME_SIZE {
+            let mut resized = false;
+            while let Ok(Some(())) = resize_events.try_next() {
+                resized = true;
+            }
+            while let Ok(Some(())) = context_restored_events.try_next() {
+                match browser::webgl2_context().and_then(WebGlRenderer::new) {
+                    Ok(restored) => {
+                        renderer = Box::new(restored);
+                        resized = true;
+                    }
+                    Err(err) => error!("Could not restore WebGL {:#?}", err),
+                }
+            }
+            if resized {
+                if let Err(err) = fit_canvas(renderer.as_ref(), resolution) {
+                    error!("Could not resize the canvas {:#?}", err);
+                }
+            }
+
+            for _ in 0..game_loop.advance(perf) {
+                input.next_tick(&mut keystate, game.is_paused());
                 game.update(&keystate);
-                game_loop.accumulated_delta -= FRAME_SIZE;
+
+                if game.is_paused() {
+                    game_loop.pause();
+                    break;
+                }
             }
 
-            game_loop.last_frame = perf;
-            game.draw(&renderer);
+            game.draw(renderer.as_ref(), game_loop.alpha());
 
             if cfg!(debug_assertions) {
                 unsafe {
-                    draw_frame_rate(&renderer, frame_time);
+                    draw_frame_rate(renderer.as_ref(), frame_time);
                 }
             }
+            renderer.present();
 
             browser::request_animation_frame(f.borrow().as_ref().unwrap()).unwrap();
         }));
@@ -59,7 +183,28 @@ impl GameLoop {
     }
 }
 
-unsafe fn draw_frame_rate(renderer: &Renderer, frame_time: f64) {
+fn create_renderer(backend: RendererBackend) -> Result<Box<dyn Renderer>> {
+    if backend == RendererBackend::WebGl {
+        match browser::webgl2_context().and_then(WebGlRenderer::new) {
+            Ok(renderer) => return Ok(Box::new(renderer)),
+            Err(err) => {
+                error!("Could not start WebGL, drawing on a 2d canvas {:#?}", err);
+                browser::replace_canvas()?;
+            }
+        }
+    }
+    Ok(Box::new(CanvasRenderer::new(browser::context()?)))
+}
+
+fn fit_canvas(renderer: &dyn Renderer, resolution: (i16, i16)) -> Result<()> {
+    renderer.resize(&Viewport::fit(
+        resolution,
+        browser::canvas_size()?,
+        browser::device_pixel_ratio()?,
+    ))
+}
+
+unsafe fn draw_frame_rate(renderer: &dyn Renderer, frame_time: f64) {
     static mut FRAMES_COUNTED: i32 = 0;
     static mut TOTAL_FRAME_TIME: f64 = 0.0;
     static mut FRAME_RATE: i32 = 0;
@@ -73,10 +218,79 @@ unsafe fn draw_frame_rate(renderer: &Renderer, frame_time: f64) {
         FRAMES_COUNTED = 0;
     }
 
+    let frame_rate = FRAME_RATE;
     if let Err(err) = renderer.draw_text(
-        &format!("Frame Rate {}", FRAME_RATE),
+        &format!("Frame Rate {}", frame_rate),
         &Point { x: 400, y: 100 },
     ) {
         error!("Could not draw text {:#?}", err);
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_one_update_per_elapsed_frame() {
+        let mut game_loop = GameLoop::new(0.0, GameLoopConfig::default());
+
+        assert_eq!(game_loop.advance(FRAME_SIZE as f64 * 2.5), 2);
+        assert!((game_loop.alpha() - 0.5).abs() < 0.01);
+        assert_eq!(game_loop.advance(FRAME_SIZE as f64 * 3.0), 1);
+    }
+
+    #[test]
+    fn a_replay_holds_its_place_while_the_game_is_paused() {
+        let platform = NativePlatform::default();
+        let recording = Recording {
+            seed: 1,
+            bindings: None,
+            keys: vec!["ArrowRight".into()],
+            ticks: vec![
+                (
+                    TickInput {
+                        held: 1,
+                        pressed: 1,
+                        released: 0,
+                    },
+                    1,
+                ),
+                (
+                    TickInput {
+                        held: 0,
+                        pressed: 0,
+                        released: 1,
+                    },
+                    1,
+                ),
+            ],
+        };
+        let mut input = Input::Replay(Replay::new(recording), platform.prepare_input().unwrap());
+        let mut keystate = KeyState::new();
+
+        platform.press_key("KeyP").unwrap();
+        input.next_tick(&mut keystate, true);
+        assert!(keystate.just_pressed("KeyP"));
+        assert!(!keystate.is_pressed("ArrowRight"));
+
+        input.next_tick(&mut keystate, false);
+        assert!(keystate.is_pressed("ArrowRight"));
+        input.next_tick(&mut keystate, false);
+        assert!(!keystate.is_pressed("ArrowRight"));
+    }
+
+    #[test]
+    fn a_long_frame_does_not_cause_a_burst_of_updates() {
+        let config = GameLoopConfig {
+            max_frame_time: 250.0,
+            max_updates_per_frame: 5,
+            ..GameLoopConfig::default()
+        };
+        let mut game_loop = GameLoop::new(0.0, config);
+
+        assert_eq!(game_loop.advance(60_000.0), 5);
+        assert!(game_loop.alpha() <= 1.0);
+        assert!(game_loop.advance(60_000.0 + FRAME_SIZE as f64) <= 2);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::browser;
+
+use anyhow::{anyhow, Result};
+use wasm_bindgen::JsCast;
+use web_sys::{Gamepad, GamepadButton};
+
+use super::KeyState;
+
+/// Gamepad input shows up in the `KeyState` under these codes.
+pub const GAMEPAD_PREFIX: &str = "Gamepad";
+const AXIS_THRESHOLD: f64 = 0.5;
+
+pub fn button_code(index: u32) -> String {
+    format!("{}Button{}", GAMEPAD_PREFIX, index)
+}
+
+pub fn axis_code(direction: &str) -> String {
+    format!("{}Axis{}", GAMEPAD_PREFIX, direction)
+}
+
+pub(super) fn poll_gamepads(state: &mut KeyState) {
+    match pressed_gamepad_codes() {
+        Ok(codes) => apply_gamepad_codes(state, &codes),
+        Err(err) => error!("Could not read gamepads {:#?}", err),
+    }
+}
+
+fn pressed_gamepad_codes() -> Result<Vec<String>> {
+    let gamepads = browser::window()?
+        .navigator()
+        .get_gamepads()
+        .map_err(|e| anyhow!("Could not get gamepads {:#?}", e))?;
+
+    let mut codes = vec![];
+    for gamepad in gamepads
+        .iter()
+        .filter_map(|gamepad| gamepad.dyn_into::<Gamepad>().ok())
+    {
+        for (index, button) in gamepad.buttons().iter().enumerate() {
+            if button
+                .dyn_into::<GamepadButton>()
+                .map(|button| button.pressed())
+                .unwrap_or(false)
+            {
+                codes.push(button_code(index as u32));
+            }
+        }
+
+        let axes: Vec<f64> = gamepad
+            .axes()
+            .iter()
+            .filter_map(|axis| axis.as_f64())
+            .collect();
+        if let [horizontal, vertical, ..] = axes[..] {
+            for (value, negative, positive) in
+                [(horizontal, "Left", "Right"), (vertical, "Up", "Down")]
+            {
+                if value <= -AXIS_THRESHOLD {
+                    codes.push(axis_code(negative));
+                } else if value >= AXIS_THRESHOLD {
+                    codes.push(axis_code(positive));
+                }
+            }
+        }
+    }
+    Ok(codes)
+}
+
+/// Gamepads are polled, so each poll replaces all the gamepad codes.
+fn apply_gamepad_codes(state: &mut KeyState, codes: &[String]) {
+    let released: Vec<String> = state
+        .pressed_keys()
+        .filter(|code| code.starts_with(GAMEPAD_PREFIX) && !codes.iter().any(|held| held == code))
+        .map(String::from)
+        .collect();
+    released.iter().for_each(|code| state.set_released(code));
+    codes.iter().for_each(|code| state.set_pressed(code));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gamepad_codes_replace_the_previous_poll_and_leave_keys_alone() {
+        let mut state = KeyState::new();
+        state.set_pressed("Space");
+
+        apply_gamepad_codes(&mut state, &[button_code(0), axis_code("Right")]);
+        assert!(state.is_pressed("GamepadButton0"));
+        assert!(state.is_pressed("GamepadAxisRight"));
+
+        apply_gamepad_codes(&mut state, &[button_code(13)]);
+        assert!(!state.is_pressed("GamepadButton0"));
+        assert!(!state.is_pressed("GamepadAxisRight"));
+        assert!(state.is_pressed("GamepadButton13"));
+        assert!(state.is_pressed("Space"));
+
+        state.start_tick();
+        apply_gamepad_codes(&mut state, &[button_code(13)]);
+        assert!(!state.just_pressed("GamepadButton13"));
+        assert_eq!(state.ticks_held("GamepadButton13"), 2);
+    }
+}
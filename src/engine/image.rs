@@ -1,14 +1,12 @@
-use web_sys::HtmlImageElement;
-
-use super::{Point, Rect, Renderer};
+use super::{ImageElement, Point, Rect, Renderer};
 
 pub(crate) struct Image {
-    element: HtmlImageElement,
+    element: ImageElement,
     bounding_box: Rect,
 }
 
 impl Image {
-    pub(crate) fn new(element: HtmlImageElement, position: Point) -> Self {
+    pub(crate) fn new(element: ImageElement, position: Point) -> Self {
         let bounding_box = Rect::new(position, element.width() as i16, element.height() as i16);
 
         Self {
@@ -17,8 +15,8 @@ impl Image {
         }
     }
 
-    pub(crate) fn draw(&self, renderer: &Renderer) {
-        renderer.draw_entire_image(&self.element, &self.bounding_box().position);
+    pub(crate) fn draw(&self, renderer: &dyn Renderer) {
+        renderer.draw_entire_image(self.element.as_ref(), &self.bounding_box().position);
     }
 
     pub(crate) fn bounding_box(&self) -> &Rect {
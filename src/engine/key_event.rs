@@ -1,36 +1,103 @@
 use crate::browser;
 
+use super::add_touch_handlers;
+
 use anyhow::Result;
 use futures::channel::mpsc;
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 use wasm_bindgen::JsCast;
 
-pub(super) enum KeyPress {
-    KeyDown(web_sys::KeyboardEvent),
-    KeyUp(web_sys::KeyboardEvent),
+pub enum KeyPress {
+    KeyDown(String),
+    KeyUp(String),
 }
 
+/// Held keys map to the number of ticks they have been held.
+#[derive(Default, Clone)]
 pub struct KeyState {
-    pressed_keys: HashMap<String, web_sys::KeyboardEvent>,
+    pressed_keys: HashMap<String, u32>,
+    just_pressed: HashSet<String>,
+    just_released: HashSet<String>,
 }
 
 impl KeyState {
     pub fn new() -> Self {
         KeyState {
             pressed_keys: HashMap::new(),
+            just_pressed: HashSet::new(),
+            just_released: HashSet::new(),
         }
     }
 
     pub fn is_pressed(&self, code: &str) -> bool {
-        self.pressed_keys.contains_key(code)
+        self.ticks_held(code) > 0
+    }
+
+    /// Also true if the key was let go again within the tick.
+    pub fn just_pressed(&self, code: &str) -> bool {
+        self.just_pressed.contains(code)
+    }
+
+    pub fn just_released(&self, code: &str) -> bool {
+        self.just_released.contains(code)
+    }
+
+    pub fn ticks_held(&self, code: &str) -> u32 {
+        self.pressed_keys.get(code).copied().unwrap_or(0)
+    }
+
+    pub fn pressed_keys(&self) -> impl Iterator<Item = &str> {
+        self.pressed_keys.keys().map(String::as_str)
+    }
+
+    pub fn just_pressed_keys(&self) -> impl Iterator<Item = &str> {
+        self.just_pressed.iter().map(String::as_str)
     }
 
-    fn set_pressed(&mut self, code: &str, event: web_sys::KeyboardEvent) {
-        self.pressed_keys.insert(code.into(), event);
+    pub fn just_released_keys(&self) -> impl Iterator<Item = &str> {
+        self.just_released.iter().map(String::as_str)
     }
 
-    fn set_released(&mut self, code: &str) {
+    pub(crate) fn set_pressed(&mut self, code: &str) {
+        if !self.pressed_keys.contains_key(code) {
+            self.pressed_keys.insert(code.into(), 1);
+            self.just_pressed.insert(code.into());
+        }
+    }
+
+    pub(crate) fn set_released(&mut self, code: &str) {
+        if self.pressed_keys.remove(code).is_some() {
+            self.just_released.insert(code.into());
+        }
+    }
+
+    pub(crate) fn forget(&mut self, code: &str) {
         self.pressed_keys.remove(code);
+        self.just_pressed.remove(code);
+        self.just_released.remove(code);
+    }
+
+    pub(crate) fn start_tick(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+        self.pressed_keys.values_mut().for_each(|ticks| *ticks += 1);
+    }
+
+    /// For input that arrives as snapshots rather than events.
+    pub(crate) fn update_pressed<'a>(&mut self, codes: impl IntoIterator<Item = &'a str>) {
+        self.start_tick();
+        let codes: HashSet<&str> = codes.into_iter().collect();
+        let released: Vec<String> = self
+            .pressed_keys()
+            .filter(|code| !codes.contains(code))
+            .map(String::from)
+            .collect();
+        released.iter().for_each(|code| self.set_released(code));
+        codes.into_iter().for_each(|code| self.set_pressed(code));
     }
 }
 
@@ -38,11 +105,12 @@ pub(super) fn prepare_input() -> Result<mpsc::UnboundedReceiver<KeyPress>> {
     let (keyevent_sender, keyevent_receiver) = mpsc::unbounded();
     let keydown_sender = Rc::new(RefCell::new(keyevent_sender));
     let keyup_sender = Rc::clone(&keydown_sender);
+    add_touch_handlers(Rc::clone(&keydown_sender))?;
 
     let onkeydown = browser::closure_wrap(Box::new(move |keycode: web_sys::KeyboardEvent| {
         if let Err(err) = keydown_sender
             .borrow_mut()
-            .start_send(KeyPress::KeyDown(keycode))
+            .start_send(KeyPress::KeyDown(keycode.code()))
         {
             error!("Could not send keyDown message {:#?}", err);
         }
@@ -51,7 +119,7 @@ pub(super) fn prepare_input() -> Result<mpsc::UnboundedReceiver<KeyPress>> {
     let onkeyup = browser::closure_wrap(Box::new(move |keycode: web_sys::KeyboardEvent| {
         if let Err(err) = keyup_sender
             .borrow_mut()
-            .start_send(KeyPress::KeyUp(keycode))
+            .start_send(KeyPress::KeyUp(keycode.code()))
         {
             error!("Could not send keyUp message {:#?}", err);
         }
@@ -69,18 +137,95 @@ pub(super) fn process_input(
     state: &mut KeyState,
     keyevent_receiver: &mut mpsc::UnboundedReceiver<KeyPress>,
 ) {
+    state.start_tick();
     loop {
         match keyevent_receiver.try_next() {
             Ok(None) => break,
             Err(_err) => break,
             Ok(Some(evt)) => match evt {
-                KeyPress::KeyUp(evt) => {
-                    state.set_released(&evt.code());
+                KeyPress::KeyUp(code) => {
+                    state.set_released(&code);
                 }
-                KeyPress::KeyDown(evt) => {
-                    state.set_pressed(&evt.code(), evt);
+                KeyPress::KeyDown(code) => {
+                    state.set_pressed(&code);
                 }
             },
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{NativePlatform, Platform};
+
+    #[test]
+    fn process_input_applies_key_presses_in_order() {
+        let platform = NativePlatform::default();
+        let mut receiver = platform.prepare_input().unwrap();
+        let mut state = KeyState::new();
+
+        platform.press_key("Space").unwrap();
+        platform.press_key("ArrowRight").unwrap();
+        platform.release_key("Space").unwrap();
+        process_input(&mut state, &mut receiver);
+
+        assert!(!state.is_pressed("Space"));
+        assert!(state.is_pressed("ArrowRight"));
+    }
+
+    #[test]
+    fn tracks_transitions_and_hold_duration_per_tick() {
+        let platform = NativePlatform::default();
+        let mut receiver = platform.prepare_input().unwrap();
+        let mut state = KeyState::new();
+
+        platform.press_key("Space").unwrap();
+        process_input(&mut state, &mut receiver);
+        assert!(state.just_pressed("Space"));
+        assert_eq!(state.ticks_held("Space"), 1);
+
+        platform.press_key("Space").unwrap();
+        process_input(&mut state, &mut receiver);
+        assert!(!state.just_pressed("Space"));
+        assert_eq!(state.ticks_held("Space"), 2);
+
+        platform.release_key("Space").unwrap();
+        process_input(&mut state, &mut receiver);
+        assert!(state.just_released("Space"));
+        assert_eq!(state.ticks_held("Space"), 0);
+
+        process_input(&mut state, &mut receiver);
+        assert!(!state.just_released("Space"));
+    }
+
+    #[test]
+    fn a_tap_within_one_tick_is_still_seen() {
+        let platform = NativePlatform::default();
+        let mut receiver = platform.prepare_input().unwrap();
+        let mut state = KeyState::new();
+
+        platform.press_key("Space").unwrap();
+        platform.release_key("Space").unwrap();
+        process_input(&mut state, &mut receiver);
+
+        assert!(state.just_pressed("Space"));
+        assert!(state.just_released("Space"));
+        assert!(!state.is_pressed("Space"));
+    }
+
+    #[test]
+    fn snapshots_produce_the_same_transitions_as_events() {
+        let mut state = KeyState::new();
+
+        state.update_pressed(["ArrowRight"]);
+        state.update_pressed(["ArrowRight", "Space"]);
+        assert!(state.just_pressed("Space"));
+        assert!(!state.just_pressed("ArrowRight"));
+        assert_eq!(state.ticks_held("ArrowRight"), 2);
+
+        state.update_pressed(["Space"]);
+        assert!(state.just_released("ArrowRight"));
+        assert_eq!(state.ticks_held("Space"), 2);
+    }
+}
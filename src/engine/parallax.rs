@@ -0,0 +1,187 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::{Camera, ImageElement, Platform, Point, Rect, Renderer};
+
+#[derive(Deserialize)]
+pub(crate) struct Scene {
+    layers: Vec<LayerDefinition>,
+}
+
+/// A scroll factor below 1 makes the layer look further away.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LayerDefinition {
+    images: Vec<String>,
+    #[serde(default = "default_scroll_factor")]
+    scroll_factor: f32,
+    #[serde(default)]
+    y: i16,
+}
+
+fn default_scroll_factor() -> f32 {
+    1.0
+}
+
+struct Layer {
+    images: Vec<ImageElement>,
+    width: i16,
+    scroll_factor: f32,
+    y: i16,
+    offset: f32,
+}
+
+impl Layer {
+    fn positions(&self, visible: &Rect) -> Vec<(&ImageElement, Point)> {
+        let mut x = visible.x() - self.offset.rem_euclid(f32::from(self.width)).round() as i16;
+        let mut positions = vec![];
+        for image in self.images.iter().cycle() {
+            if x >= visible.right() {
+                break;
+            }
+            positions.push((image, Point { x, y: self.y }));
+            x += image.width() as i16;
+        }
+        positions
+    }
+}
+
+pub(crate) struct Parallax {
+    layers: Vec<Layer>,
+}
+
+impl Parallax {
+    pub(crate) async fn load(platform: &dyn Platform, json_path: &str) -> Result<Self> {
+        let scene = super::load_json::<Scene>(platform, json_path).await?;
+
+        let mut images = HashMap::new();
+        for image in scene.layers.iter().flat_map(|layer| &layer.images) {
+            if !images.contains_key(image) {
+                images.insert(image.to_string(), platform.load_image(image).await?);
+            }
+        }
+
+        Self::new(scene, images)
+    }
+
+    pub(crate) fn new(scene: Scene, images: HashMap<String, ImageElement>) -> Result<Self> {
+        let layers = scene
+            .layers
+            .into_iter()
+            .enumerate()
+            .map(|(index, layer)| {
+                if layer.scroll_factor < 0.0 {
+                    return Err(anyhow!("Layer {} has a negative scroll factor", index));
+                }
+                let images = layer
+                    .images
+                    .iter()
+                    .map(|name| {
+                        images
+                            .get(name)
+                            .cloned()
+                            .ok_or_else(|| anyhow!("Layer {} uses unknown image {}", index, name))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let width = images.iter().map(|image| image.width() as i16).sum();
+                if images.iter().any(|image| image.width() == 0) || width == 0 {
+                    return Err(anyhow!("Layer {} needs images with a width", index));
+                }
+                Ok(Layer {
+                    images,
+                    width,
+                    scroll_factor: layer.scroll_factor,
+                    y: layer.y,
+                    offset: 0.0,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Parallax { layers })
+    }
+
+    /// Positions wrap within one repeat, so they never overflow.
+    pub(crate) fn scroll(&mut self, distance: f32) {
+        self.layers.iter_mut().for_each(|layer| {
+            layer.offset =
+                (layer.offset + distance * layer.scroll_factor).rem_euclid(f32::from(layer.width));
+        });
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.layers.iter_mut().for_each(|layer| layer.offset = 0.0);
+    }
+
+    pub(crate) fn draw(&self, renderer: &dyn Renderer, camera: &Camera) {
+        let visible = camera.visible();
+        for layer in &self.layers {
+            for (image, position) in layer.positions(&visible) {
+                renderer.draw_entire_image(image.as_ref(), &position);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::NativePlatform;
+    use futures::executor::block_on;
+
+    fn scene(json: &str) -> Result<Parallax> {
+        let platform = NativePlatform::default();
+        let image = block_on(platform.load_image("Stone.png")).unwrap();
+        let images = HashMap::from([("Stone.png".to_string(), image)]);
+        Parallax::new(serde_json::from_str(json).unwrap(), images)
+    }
+
+    fn xs(parallax: &Parallax, layer: usize, visible: &Rect) -> Vec<i16> {
+        parallax.layers[layer]
+            .positions(visible)
+            .iter()
+            .map(|(_, position)| position.x)
+            .collect()
+    }
+
+    #[test]
+    fn layers_scroll_at_their_own_speed_and_wrap() {
+        let mut parallax = scene(
+            r#"{ "layers": [
+                { "images": ["Stone.png"] },
+                { "images": ["Stone.png"], "scrollFactor": 0.5, "y": 40 }
+            ] }"#,
+        )
+        .unwrap();
+        let width = parallax.layers[0].width;
+        let visible = Rect::new_from_x_y(1000, 0, width * 2, 600);
+
+        assert_eq!(xs(&parallax, 0, &visible), vec![1000, 1000 + width]);
+
+        parallax.scroll(f32::from(width) + 10.0);
+        assert_eq!(
+            xs(&parallax, 0, &visible),
+            vec![990, 990 + width, 990 + width * 2]
+        );
+        assert_eq!(xs(&parallax, 1, &visible)[0], 1000 - (width + 10) / 2);
+        assert_eq!(parallax.layers[1].positions(&visible)[0].1.y, 40);
+    }
+
+    #[test]
+    fn rejects_layers_with_unknown_images() {
+        let error = scene(r#"{ "layers": [{ "images": ["Clouds.png"] }] }"#)
+            .err()
+            .unwrap();
+
+        assert_eq!(error.to_string(), "Layer 0 uses unknown image Clouds.png");
+    }
+
+    #[test]
+    fn loads_the_shipped_scene() {
+        let platform = NativePlatform::default();
+        let parallax = block_on(Parallax::load(&platform, "scene.json")).unwrap();
+
+        assert_eq!(parallax.layers.len(), 2);
+        assert_eq!(parallax.layers[1].width, 1000);
+    }
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod native;
+mod web;
+
+#[cfg(test)]
+pub use native::*;
+pub use web::*;
+
+use super::{Audio, KeyPress, Sound};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::channel::mpsc::UnboundedReceiver;
+use serde::de::DeserializeOwned;
+use std::{any::Any, rc::Rc};
+
+pub trait ImageSource {
+    fn width(&self) -> u32;
+    fn height(&self) -> u32;
+    fn as_any(&self) -> &dyn Any;
+}
+
+pub type ImageElement = Rc<dyn ImageSource>;
+
+pub trait SoundSource {
+    fn as_any(&self) -> &dyn Any;
+}
+
+#[async_trait(?Send)]
+pub trait AudioDevice {
+    async fn load_sound(&self, filename: &str) -> Result<Sound>;
+    fn play_sound(&self, sound: &Sound) -> Result<()>;
+    fn play_looping_sound(&self, sound: &Sound) -> Result<()>;
+    fn suspend(&self) -> Result<()>;
+    fn resume(&self) -> Result<()>;
+}
+
+#[async_trait(?Send)]
+pub trait Platform {
+    async fn load_image(&self, source: &str) -> Result<ImageElement>;
+    async fn fetch_json(&self, json_path: &str) -> Result<serde_json::Value>;
+    fn create_audio(&self) -> Result<Audio>;
+    fn prepare_input(&self) -> Result<UnboundedReceiver<KeyPress>>;
+    fn draw_ui(&self, html: &str) -> Result<()>;
+    fn hide_ui(&self) -> Result<()>;
+    fn add_click_handler(&self, id: &str) -> Result<UnboundedReceiver<()>>;
+    fn add_visibility_handler(&self, audio: Audio) -> Result<UnboundedReceiver<bool>>;
+    fn load_item(&self, key: &str) -> Result<Option<String>>;
+    fn save_item(&self, key: &str, value: &str) -> Result<()>;
+}
+
+pub async fn load_json<T: DeserializeOwned>(platform: &dyn Platform, json_path: &str) -> Result<T> {
+    serde_json::from_value(platform.fetch_json(json_path).await?)
+        .map_err(|e| anyhow!("Failed to convert {} {:#?}", json_path, e))
+}
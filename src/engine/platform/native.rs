@@ -0,0 +1,273 @@
+use super::{AudioDevice, ImageElement, ImageSource, Platform, SoundSource};
+use crate::engine::{Audio, KeyPress, Sound};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    rc::Rc,
+};
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// A headless platform that reads assets from disk, for `cargo test`.
+pub struct NativePlatform {
+    asset_root: PathBuf,
+    audio: Rc<NativeAudio>,
+    ui: RefCell<Vec<String>>,
+    key_sender: RefCell<Option<UnboundedSender<KeyPress>>>,
+    click_senders: RefCell<HashMap<String, UnboundedSender<()>>>,
+    storage: RefCell<HashMap<String, String>>,
+    visibility_sender: RefCell<Option<(UnboundedSender<bool>, Audio)>>,
+}
+
+impl NativePlatform {
+    pub fn new(asset_root: impl Into<PathBuf>) -> Self {
+        NativePlatform {
+            asset_root: asset_root.into(),
+            audio: Rc::new(NativeAudio::default()),
+            ui: RefCell::new(vec![]),
+            key_sender: RefCell::new(None),
+            click_senders: RefCell::new(HashMap::new()),
+            storage: RefCell::new(HashMap::new()),
+            visibility_sender: RefCell::new(None),
+        }
+    }
+
+    pub fn audio(&self) -> &NativeAudio {
+        &self.audio
+    }
+
+    pub fn ui(&self) -> Vec<String> {
+        self.ui.borrow().clone()
+    }
+
+    pub fn press_key(&self, code: &str) -> Result<()> {
+        self.send_key(KeyPress::KeyDown(code.into()))
+    }
+
+    pub fn release_key(&self, code: &str) -> Result<()> {
+        self.send_key(KeyPress::KeyUp(code.into()))
+    }
+
+    pub fn click(&self, id: &str) -> Result<()> {
+        self.click_senders
+            .borrow_mut()
+            .get_mut(id)
+            .ok_or_else(|| anyhow!("No click handler for {}", id))?
+            .start_send(())
+            .map_err(|e| anyhow!("Could not send click message {:#?}", e))
+    }
+
+    pub fn set_hidden(&self, hidden: bool) -> Result<()> {
+        let mut handler = self.visibility_sender.borrow_mut();
+        let (sender, audio) = handler
+            .as_mut()
+            .ok_or_else(|| anyhow!("No visibility handler"))?;
+        if hidden {
+            audio.suspend()?;
+        }
+        sender
+            .start_send(hidden)
+            .map_err(|e| anyhow!("Could not send visibility message {:#?}", e))
+    }
+
+    fn send_key(&self, key_press: KeyPress) -> Result<()> {
+        self.key_sender
+            .borrow_mut()
+            .as_mut()
+            .ok_or_else(|| anyhow!("Input has not been prepared"))?
+            .start_send(key_press)
+            .map_err(|e| anyhow!("Could not send key message {:#?}", e))
+    }
+
+    fn read(&self, source: &str) -> Result<Vec<u8>> {
+        let path = self.asset_root.join(source);
+        fs::read(&path).map_err(|e| anyhow!("Could not read {:?} {:#?}", path, e))
+    }
+}
+
+impl Default for NativePlatform {
+    fn default() -> Self {
+        NativePlatform::new(concat!(env!("CARGO_MANIFEST_DIR"), "/static"))
+    }
+}
+
+#[async_trait(?Send)]
+impl Platform for NativePlatform {
+    async fn load_image(&self, source: &str) -> Result<ImageElement> {
+        let bytes = self.read(source)?;
+        let image = StubImage::from_png(&bytes)
+            .ok_or_else(|| anyhow!("Error Loading Image: {} is not a PNG", source))?;
+        Ok(Rc::new(image))
+    }
+
+    async fn fetch_json(&self, json_path: &str) -> Result<serde_json::Value> {
+        serde_json::from_slice(&self.read(json_path)?)
+            .map_err(|e| anyhow!("error fetchng JSON {:#?}", e))
+    }
+
+    fn create_audio(&self) -> Result<Audio> {
+        Ok(Audio::new(self.audio.clone()))
+    }
+
+    fn prepare_input(&self) -> Result<UnboundedReceiver<KeyPress>> {
+        let (sender, receiver) = unbounded();
+        self.key_sender.replace(Some(sender));
+        Ok(receiver)
+    }
+
+    fn draw_ui(&self, html: &str) -> Result<()> {
+        self.ui.borrow_mut().insert(0, html.into());
+        Ok(())
+    }
+
+    fn hide_ui(&self) -> Result<()> {
+        let mut ui = self.ui.borrow_mut();
+        if !ui.is_empty() {
+            ui.remove(0);
+        }
+        Ok(())
+    }
+
+    fn add_click_handler(&self, id: &str) -> Result<UnboundedReceiver<()>> {
+        let (sender, receiver) = unbounded();
+        self.click_senders.borrow_mut().insert(id.into(), sender);
+        Ok(receiver)
+    }
+
+    fn add_visibility_handler(&self, audio: Audio) -> Result<UnboundedReceiver<bool>> {
+        let (sender, receiver) = unbounded();
+        self.visibility_sender.replace(Some((sender, audio)));
+        Ok(receiver)
+    }
+
+    fn load_item(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.storage.borrow().get(key).cloned())
+    }
+
+    fn save_item(&self, key: &str, value: &str) -> Result<()> {
+        self.storage.borrow_mut().insert(key.into(), value.into());
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct StubImage {
+    width: u32,
+    height: u32,
+}
+
+impl StubImage {
+    pub fn new(width: u32, height: u32) -> Self {
+        StubImage { width, height }
+    }
+
+    fn from_png(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 24 || bytes[0..8] != PNG_SIGNATURE || &bytes[12..16] != b"IHDR" {
+            return None;
+        }
+
+        let width = u32::from_be_bytes(bytes[16..20].try_into().ok()?);
+        let height = u32::from_be_bytes(bytes[20..24].try_into().ok()?);
+        Some(StubImage::new(width, height))
+    }
+}
+
+impl ImageSource for StubImage {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct StubSound {
+    pub name: String,
+}
+
+impl SoundSource for StubSound {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[derive(Default)]
+pub struct NativeAudio {
+    played: RefCell<Vec<String>>,
+    suspended: Cell<bool>,
+}
+
+impl NativeAudio {
+    pub fn played(&self) -> Vec<String> {
+        self.played.borrow().clone()
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.suspended.get()
+    }
+
+    fn record(&self, sound: &Sound) -> Result<()> {
+        let sound = sound
+            .buffer
+            .as_any()
+            .downcast_ref::<StubSound>()
+            .ok_or_else(|| anyhow!("Sound was not loaded by the native audio backend"))?;
+        self.played.borrow_mut().push(sound.name.clone());
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl AudioDevice for NativeAudio {
+    async fn load_sound(&self, filename: &str) -> Result<Sound> {
+        Ok(Sound {
+            buffer: Rc::new(StubSound {
+                name: filename.into(),
+            }),
+        })
+    }
+
+    fn play_sound(&self, sound: &Sound) -> Result<()> {
+        self.record(sound)
+    }
+
+    fn play_looping_sound(&self, sound: &Sound) -> Result<()> {
+        self.record(sound)
+    }
+
+    fn suspend(&self) -> Result<()> {
+        self.suspended.set(true);
+        Ok(())
+    }
+
+    fn resume(&self) -> Result<()> {
+        self.suspended.set(false);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_image_size_from_png_header() {
+        let platform = NativePlatform::default();
+        let stone = futures::executor::block_on(platform.load_image("Stone.png")).unwrap();
+
+        assert_eq!(stone.width(), 90);
+        assert_eq!(stone.height(), 54);
+    }
+}
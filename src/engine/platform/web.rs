@@ -0,0 +1,136 @@
+use super::{AudioDevice, ImageElement, ImageSource, Platform, SoundSource};
+use crate::{
+    browser,
+    engine::{self, Audio, KeyPress, Sound},
+    sound,
+};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::channel::mpsc::UnboundedReceiver;
+use serde_wasm_bindgen::from_value;
+use std::{any::Any, rc::Rc};
+use web_sys::{AudioBuffer, AudioContext, HtmlImageElement};
+
+pub struct WebPlatform;
+
+#[async_trait(?Send)]
+impl Platform for WebPlatform {
+    async fn load_image(&self, source: &str) -> Result<ImageElement> {
+        Ok(Rc::new(engine::load_image(source).await?))
+    }
+
+    async fn fetch_json(&self, json_path: &str) -> Result<serde_json::Value> {
+        from_value(browser::fetch_json(json_path).await?)
+            .map_err(|e| anyhow!("Failed to converting json {:#?}", e))
+    }
+
+    fn create_audio(&self) -> Result<Audio> {
+        Ok(Audio::new(Rc::new(WebAudio::new()?)))
+    }
+
+    fn prepare_input(&self) -> Result<UnboundedReceiver<KeyPress>> {
+        engine::prepare_input()
+    }
+
+    fn draw_ui(&self, html: &str) -> Result<()> {
+        browser::draw_ui(html)
+    }
+
+    fn hide_ui(&self) -> Result<()> {
+        browser::hide_ui()
+    }
+
+    fn add_click_handler(&self, id: &str) -> Result<UnboundedReceiver<()>> {
+        browser::find_html_element_by_id(id).map(engine::add_click_handler)
+    }
+
+    fn add_visibility_handler(&self, audio: Audio) -> Result<UnboundedReceiver<bool>> {
+        engine::add_visibility_handler(audio)
+    }
+
+    fn load_item(&self, key: &str) -> Result<Option<String>> {
+        browser::local_storage()?
+            .get_item(key)
+            .map_err(|e| anyhow!("Could not read {} from local storage {:#?}", key, e))
+    }
+
+    fn save_item(&self, key: &str, value: &str) -> Result<()> {
+        browser::local_storage()?
+            .set_item(key, value)
+            .map_err(|e| anyhow!("Could not write {} to local storage {:#?}", key, e))
+    }
+}
+
+pub struct WebAudio {
+    context: AudioContext,
+}
+
+impl WebAudio {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            context: sound::create_audio_context()?,
+        })
+    }
+
+    fn buffer(sound: &Sound) -> Result<&AudioBuffer> {
+        sound
+            .buffer
+            .as_any()
+            .downcast_ref::<AudioBuffer>()
+            .ok_or_else(|| anyhow!("Sound was not loaded by the web audio backend"))
+    }
+}
+
+#[async_trait(?Send)]
+impl AudioDevice for WebAudio {
+    async fn load_sound(&self, filename: &str) -> Result<Sound> {
+        let array_buffer = browser::fetch_array_buffer(filename).await?;
+        let audio_buffer = sound::decode_audio_data(&self.context, &array_buffer).await?;
+        Ok(Sound {
+            buffer: Rc::new(audio_buffer),
+        })
+    }
+
+    fn play_sound(&self, sound: &Sound) -> Result<()> {
+        sound::play_sound(&self.context, Self::buffer(sound)?, sound::Looping::No)
+    }
+
+    fn play_looping_sound(&self, sound: &Sound) -> Result<()> {
+        sound::play_sound(&self.context, Self::buffer(sound)?, sound::Looping::Yes)
+    }
+
+    fn suspend(&self) -> Result<()> {
+        self.context
+            .suspend()
+            .map(|_| ())
+            .map_err(|e| anyhow!("Could not suspend audio {:#?}", e))
+    }
+
+    fn resume(&self) -> Result<()> {
+        self.context
+            .resume()
+            .map(|_| ())
+            .map_err(|e| anyhow!("Could not resume audio {:#?}", e))
+    }
+}
+
+impl ImageSource for HtmlImageElement {
+    fn width(&self) -> u32 {
+        HtmlImageElement::width(self)
+    }
+
+    fn height(&self) -> u32 {
+        HtmlImageElement::height(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl SoundSource for AudioBuffer {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -1,6 +1,13 @@
 use super::Point;
 
-#[derive(Default)]
+/// `time` is the fraction of the move made before contact.
+#[derive(Debug, PartialEq)]
+pub struct Contact {
+    pub time: f32,
+    pub normal: Point,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Rect {
     pub position: Point,
     pub width: i16,
@@ -46,6 +53,68 @@ impl Rect {
     pub fn set_x(&mut self, x: i16) {
         self.position.x = x;
     }
+
+    /// The first contact while moving by `velocity`, so a fast rect can't skip
+    /// over a thin one.
+    pub fn sweep(&self, velocity: &Point, other: &Rect) -> Option<Contact> {
+        let (entry_x, exit_x) =
+            axis_times(self.x(), self.right(), other.x(), other.right(), velocity.x)?;
+        let (entry_y, exit_y) = axis_times(
+            self.y(),
+            self.bottom(),
+            other.y(),
+            other.bottom(),
+            velocity.y,
+        )?;
+        let entry = entry_x.max(entry_y);
+        let exit = exit_x.min(exit_y);
+        if entry >= exit || entry > 1.0 || exit <= 0.0 {
+            return None;
+        }
+
+        let normal = if entry_x > entry_y {
+            Point {
+                x: -velocity.x.signum(),
+                y: 0,
+            }
+        } else {
+            Point {
+                x: 0,
+                y: -velocity.y.signum(),
+            }
+        };
+        Some(Contact {
+            time: entry.max(0.0),
+            normal,
+        })
+    }
+}
+
+fn axis_times(
+    min: i16,
+    max: i16,
+    other_min: i16,
+    other_max: i16,
+    velocity: i16,
+) -> Option<(f32, f32)> {
+    let velocity = f32::from(velocity);
+    let (min, max, other_min, other_max) = (
+        f32::from(min),
+        f32::from(max),
+        f32::from(other_min),
+        f32::from(other_max),
+    );
+    if velocity == 0.0 {
+        if max <= other_min || min >= other_max {
+            None
+        } else {
+            Some((f32::NEG_INFINITY, f32::INFINITY))
+        }
+    } else if velocity > 0.0 {
+        Some(((other_min - max) / velocity, (other_max - min) / velocity))
+    } else {
+        Some(((other_max - min) / velocity, (other_min - max) / velocity))
+    }
 }
 
 #[cfg(test)]
@@ -65,6 +134,41 @@ mod tests {
             width: 100,
         };
 
-        assert_eq!(rect2.intersects(&rect1), true);
+        assert!(rect2.intersects(&rect1));
+    }
+
+    #[test]
+    fn a_fast_rect_hits_a_thin_one_it_would_pass_through() {
+        let falling = Rect::new_from_x_y(0, 0, 10, 10);
+        let thin = Rect::new_from_x_y(0, 20, 10, 2);
+        let velocity = Point { x: 0, y: 30 };
+
+        assert!(!Rect::new_from_x_y(0, 30, 10, 10).intersects(&thin));
+        assert_eq!(
+            falling.sweep(&velocity, &thin),
+            Some(Contact {
+                time: 1.0 / 3.0,
+                normal: Point { x: 0, y: -1 }
+            })
+        );
+    }
+
+    #[test]
+    fn sweeping_sideways_into_a_rect_hits_its_side() {
+        let moving = Rect::new_from_x_y(0, 0, 10, 10);
+        let wall = Rect::new_from_x_y(15, -20, 5, 50);
+
+        let contact = moving.sweep(&Point { x: 10, y: 2 }, &wall).unwrap();
+        assert_eq!(contact.time, 0.5);
+        assert_eq!(contact.normal, Point { x: -1, y: 0 });
+    }
+
+    #[test]
+    fn sweeps_that_stop_short_or_pass_by_miss() {
+        let moving = Rect::new_from_x_y(0, 0, 10, 10);
+        let other = Rect::new_from_x_y(30, 0, 10, 10);
+
+        assert_eq!(moving.sweep(&Point { x: 10, y: 0 }, &other), None);
+        assert_eq!(moving.sweep(&Point { x: 40, y: 20 }, &other), None);
     }
 }
@@ -1,63 +1,49 @@
-use anyhow::{anyhow, Result};
-use web_sys::{CanvasRenderingContext2d, HtmlImageElement};
-
-use super::{Point, Rect};
-
-pub struct Renderer {
-    context: CanvasRenderingContext2d,
+mod canvas;
+#[cfg(test)]
+mod recording;
+mod webgl;
+
+pub use canvas::*;
+#[cfg(test)]
+pub use recording::*;
+pub use webgl::*;
+
+use super::{Camera, ImageSource, Point, Rect, Viewport};
+
+use anyhow::Result;
+use std::sync::Once;
+use web_sys::HtmlImageElement;
+
+/// WebGL falls back to the canvas without WebGL2.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RendererBackend {
+    #[default]
+    Canvas,
+    WebGl,
 }
 
-impl Renderer {
-    pub fn new(context: CanvasRenderingContext2d) -> Self {
-        Renderer { context }
-    }
-
-    pub fn clear(&self, rect: &Rect) {
-        self.context.clear_rect(
-            rect.x().into(),
-            rect.y().into(),
-            rect.width.into(),
-            rect.height.into(),
-        );
-    }
-
-    pub fn draw_image(&self, image: &HtmlImageElement, frame: &Rect, destination: &Rect) {
-        self.context
-            .draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
-                image,
-                frame.x().into(),
-                frame.y().into(),
-                frame.width.into(),
-                frame.height.into(),
-                destination.x().into(),
-                destination.y().into(),
-                destination.width.into(),
-                destination.height.into(),
-            )
-            .expect("Drawing is throwing exceptions! Unrecoverable error.");
-    }
-
-    pub fn draw_entire_image(&self, image: &HtmlImageElement, position: &Point) {
-        self.context
-            .draw_image_with_html_image_element(image, position.x.into(), position.y.into())
-            .expect("Drawing is throwing exceptions! Unrecoverable error.");
-    }
+/// Images and rects are in world coordinates, seen through the camera. Text
+/// and `clear` are in logical screen pixels.
+pub trait Renderer {
+    fn resize(&self, viewport: &Viewport) -> Result<()>;
+    fn set_camera(&self, camera: &Camera);
+    fn reset_camera(&self);
+    fn clear(&self, rect: &Rect);
+    fn draw_image(&self, image: &dyn ImageSource, frame: &Rect, destination: &Rect);
+    fn draw_entire_image(&self, image: &dyn ImageSource, position: &Point);
+    fn draw_rect(&self, bounding_box: &Rect);
+    fn draw_text(&self, text: &str, location: &Point) -> Result<()>;
+    /// Backends that batch draw whatever is still queued.
+    fn present(&self) {}
+}
 
-    pub fn draw_rect(&self, bounding_box: &Rect) {
-        self.context.set_stroke_style_str("FF0000");
-        self.context.rect(
-            bounding_box.x().into(),
-            bounding_box.y().into(),
-            bounding_box.width.into(),
-            bounding_box.height.into(),
-        );
-    }
+/// Other images are skipped, which is reported once rather than every frame.
+fn html_image(image: &dyn ImageSource) -> Option<&HtmlImageElement> {
+    static REPORTED: Once = Once::new();
 
-    pub fn draw_text(&self, text: &str, location: &Point) -> Result<()> {
-        self.context.set_font("16pt serif");
-        self.context
-            .fill_text(text, location.x.into(), location.y.into())
-            .map_err(|e| anyhow!("Error filling text {:#?}", e))?;
-        Ok(())
+    let html_image = image.as_any().downcast_ref::<HtmlImageElement>();
+    if html_image.is_none() {
+        REPORTED.call_once(|| error!("Skipping an image that isn't an HtmlImageElement"));
     }
+    html_image
 }
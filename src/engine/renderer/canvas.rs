@@ -0,0 +1,132 @@
+use anyhow::{anyhow, Result};
+use std::cell::Cell;
+use web_sys::CanvasRenderingContext2d;
+
+use super::{html_image, Renderer};
+use crate::engine::{Camera, ImageSource, Point, Rect, Viewport};
+
+pub struct CanvasRenderer {
+    context: CanvasRenderingContext2d,
+    camera: Cell<Point>,
+}
+
+impl CanvasRenderer {
+    pub fn new(context: CanvasRenderingContext2d) -> Self {
+        CanvasRenderer {
+            context,
+            camera: Cell::new(Point::default()),
+        }
+    }
+
+    fn to_screen(&self, position: &Point) -> Point {
+        let camera = self.camera.get();
+        Point {
+            x: position.x - camera.x,
+            y: position.y - camera.y,
+        }
+    }
+}
+
+impl Renderer for CanvasRenderer {
+    fn resize(&self, viewport: &Viewport) -> Result<()> {
+        if let Some(canvas) = self.context.canvas() {
+            canvas.set_width(viewport.width);
+            canvas.set_height(viewport.height);
+        }
+        self.context
+            .set_transform(
+                viewport.scale,
+                0.0,
+                0.0,
+                viewport.scale,
+                viewport.x,
+                viewport.y,
+            )
+            .map_err(|e| anyhow!("Error scaling the canvas {:#?}", e))?;
+        self.context.begin_path();
+        self.context.rect(
+            0.0,
+            0.0,
+            viewport.resolution.0.into(),
+            viewport.resolution.1.into(),
+        );
+        self.context.clip();
+        Ok(())
+    }
+
+    fn set_camera(&self, camera: &Camera) {
+        self.camera.set(camera.offset());
+    }
+
+    fn reset_camera(&self) {
+        self.camera.set(Point::default());
+    }
+
+    fn clear(&self, rect: &Rect) {
+        self.context.clear_rect(
+            rect.x().into(),
+            rect.y().into(),
+            rect.width.into(),
+            rect.height.into(),
+        );
+    }
+
+    fn draw_image(&self, image: &dyn ImageSource, frame: &Rect, destination: &Rect) {
+        let Some(image) = html_image(image) else {
+            return;
+        };
+        let position = self.to_screen(&destination.position);
+
+        if let Err(err) = self
+            .context
+            .draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                image,
+                frame.x().into(),
+                frame.y().into(),
+                frame.width.into(),
+                frame.height.into(),
+                position.x.into(),
+                position.y.into(),
+                destination.width.into(),
+                destination.height.into(),
+            )
+        {
+            error!("Could not draw image {:#?}", err);
+        }
+    }
+
+    fn draw_entire_image(&self, image: &dyn ImageSource, position: &Point) {
+        let Some(image) = html_image(image) else {
+            return;
+        };
+        let position = self.to_screen(position);
+
+        if let Err(err) = self.context.draw_image_with_html_image_element(
+            image,
+            position.x.into(),
+            position.y.into(),
+        ) {
+            error!("Could not draw image {:#?}", err);
+        }
+    }
+
+    fn draw_rect(&self, bounding_box: &Rect) {
+        let position = self.to_screen(&bounding_box.position);
+        self.context.set_stroke_style_str("#FF0000");
+        self.context.set_line_width(1.0);
+        self.context.stroke_rect(
+            f64::from(position.x) + 0.5,
+            f64::from(position.y) + 0.5,
+            f64::from(bounding_box.width) - 1.0,
+            f64::from(bounding_box.height) - 1.0,
+        );
+    }
+
+    fn draw_text(&self, text: &str, location: &Point) -> Result<()> {
+        self.context.set_font("16pt serif");
+        self.context
+            .fill_text(text, location.x.into(), location.y.into())
+            .map_err(|e| anyhow!("Error filling text {:#?}", e))?;
+        Ok(())
+    }
+}
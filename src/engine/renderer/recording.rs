@@ -0,0 +1,170 @@
+use super::Renderer;
+use crate::engine::{Camera, ImageSource, Point, Rect, Viewport};
+
+use anyhow::Result;
+use std::{cell::RefCell, env, fmt, fs, path::PathBuf};
+
+/// Images are told apart by their size.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCommand {
+    Resize(Viewport),
+    SetCamera(Point),
+    ResetCamera,
+    Clear(Rect),
+    Image {
+        size: (u32, u32),
+        frame: Rect,
+        destination: Rect,
+    },
+    EntireImage {
+        size: (u32, u32),
+        position: Point,
+    },
+    Rect(Rect),
+    Text {
+        text: String,
+        location: Point,
+    },
+}
+
+struct Area<'a>(&'a Rect);
+
+impl fmt::Display for Area<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "({}, {}) {}x{}",
+            self.0.x(),
+            self.0.y(),
+            self.0.width,
+            self.0.height
+        )
+    }
+}
+
+impl fmt::Display for DrawCommand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DrawCommand::Resize(viewport) => write!(
+                f,
+                "resize {}x{} scale {} at ({}, {})",
+                viewport.width, viewport.height, viewport.scale, viewport.x, viewport.y
+            ),
+            DrawCommand::SetCamera(offset) => write!(f, "camera ({}, {})", offset.x, offset.y),
+            DrawCommand::ResetCamera => write!(f, "reset camera"),
+            DrawCommand::Clear(rect) => write!(f, "clear {}", Area(rect)),
+            DrawCommand::Image {
+                size,
+                frame,
+                destination,
+            } => write!(
+                f,
+                "image {}x{} {} -> {}",
+                size.0,
+                size.1,
+                Area(frame),
+                Area(destination)
+            ),
+            DrawCommand::EntireImage { size, position } => write!(
+                f,
+                "image {}x{} at ({}, {})",
+                size.0, size.1, position.x, position.y
+            ),
+            DrawCommand::Rect(rect) => write!(f, "rect {}", Area(rect)),
+            DrawCommand::Text { text, location } => {
+                write!(f, "text {:?} at ({}, {})", text, location.x, location.y)
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct RecordingRenderer {
+    commands: RefCell<Vec<DrawCommand>>,
+}
+
+impl RecordingRenderer {
+    pub fn commands(&self) -> Vec<DrawCommand> {
+        self.commands.borrow().clone()
+    }
+
+    /// Compares with `tests/snapshots/<name>.snap`, or writes it when run with
+    /// `UPDATE_SNAPSHOTS=1`. Debug outlines are left out.
+    pub fn assert_snapshot(&self, name: &str) {
+        let recorded: String = self
+            .commands
+            .borrow()
+            .iter()
+            .filter(|command| !matches!(command, DrawCommand::Rect(_)))
+            .map(|command| format!("{}\n", command))
+            .collect();
+        let path: PathBuf = [
+            env!("CARGO_MANIFEST_DIR"),
+            "tests",
+            "snapshots",
+            &format!("{}.snap", name),
+        ]
+        .iter()
+        .collect();
+
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, recorded).unwrap();
+        } else {
+            let snapshot = fs::read_to_string(&path).unwrap_or_else(|err| {
+                panic!("Could not read snapshot {}: {}", path.display(), err)
+            });
+            assert_eq!(recorded, snapshot, "{} does not match what was drawn", name);
+        }
+    }
+
+    fn record(&self, command: DrawCommand) {
+        self.commands.borrow_mut().push(command);
+    }
+}
+
+impl Renderer for RecordingRenderer {
+    fn resize(&self, viewport: &Viewport) -> Result<()> {
+        self.record(DrawCommand::Resize(*viewport));
+        Ok(())
+    }
+
+    fn set_camera(&self, camera: &Camera) {
+        self.record(DrawCommand::SetCamera(camera.offset()));
+    }
+
+    fn reset_camera(&self) {
+        self.record(DrawCommand::ResetCamera);
+    }
+
+    fn clear(&self, rect: &Rect) {
+        self.record(DrawCommand::Clear(*rect));
+    }
+
+    fn draw_image(&self, image: &dyn ImageSource, frame: &Rect, destination: &Rect) {
+        self.record(DrawCommand::Image {
+            size: (image.width(), image.height()),
+            frame: *frame,
+            destination: *destination,
+        });
+    }
+
+    fn draw_entire_image(&self, image: &dyn ImageSource, position: &Point) {
+        self.record(DrawCommand::EntireImage {
+            size: (image.width(), image.height()),
+            position: *position,
+        });
+    }
+
+    fn draw_rect(&self, bounding_box: &Rect) {
+        self.record(DrawCommand::Rect(*bounding_box));
+    }
+
+    fn draw_text(&self, text: &str, location: &Point) -> Result<()> {
+        self.record(DrawCommand::Text {
+            text: text.to_string(),
+            location: *location,
+        });
+        Ok(())
+    }
+}
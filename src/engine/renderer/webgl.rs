@@ -0,0 +1,555 @@
+use anyhow::{anyhow, Result};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement, WebGl2RenderingContext as Gl,
+    WebGlBuffer, WebGlProgram, WebGlShader, WebGlTexture, WebGlVertexArrayObject,
+};
+
+use super::{html_image, Renderer};
+use crate::browser;
+use crate::engine::{Camera, ImageSource, Point, Rect, Viewport};
+
+const VERTEX_SHADER: &str = r#"#version 300 es
+in vec2 position;
+in vec2 uv;
+out vec2 v_uv;
+
+void main() {
+    v_uv = uv;
+    gl_Position = vec4(position, 0.0, 1.0);
+}
+"#;
+
+const FRAGMENT_SHADER: &str = r#"#version 300 es
+precision mediump float;
+uniform sampler2D sprite;
+in vec2 v_uv;
+out vec4 color;
+
+void main() {
+    color = texture(sprite, v_uv);
+}
+"#;
+
+/// Position then texture coordinate, two floats each.
+const FLOATS_PER_VERTEX: usize = 4;
+const FLOATS_PER_QUAD: usize = FLOATS_PER_VERTEX * 6;
+
+const RECT_TEXTURE: &str = "#rect";
+const TEXT_TEXTURE: &str = "#text";
+const TEXT_WIDTH: i16 = 512;
+const TEXT_HEIGHT: i16 = 32;
+const TEXT_BASELINE: i16 = 24;
+
+/// Batches sprites that share an image into one draw call.
+pub struct WebGlRenderer {
+    gl: Gl,
+    program: WebGlProgram,
+    vertex_array: WebGlVertexArrayObject,
+    buffer: WebGlBuffer,
+    textures: RefCell<HashMap<String, Texture>>,
+    text_canvas: HtmlCanvasElement,
+    text_context: CanvasRenderingContext2d,
+    batch: RefCell<SpriteBatch>,
+    viewport: Cell<Viewport>,
+    camera: Cell<Point>,
+}
+
+impl WebGlRenderer {
+    pub fn new(gl: Gl) -> Result<Self> {
+        let program = link_program(&gl)?;
+        let vertex_array = gl
+            .create_vertex_array()
+            .ok_or_else(|| anyhow!("Could not create a vertex array"))?;
+        let buffer = gl
+            .create_buffer()
+            .ok_or_else(|| anyhow!("Could not create a vertex buffer"))?;
+
+        gl.bind_vertex_array(Some(&vertex_array));
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&buffer));
+        let stride = (FLOATS_PER_VERTEX * std::mem::size_of::<f32>()) as i32;
+        gl.enable_vertex_attrib_array(0);
+        gl.vertex_attrib_pointer_with_i32(0, 2, Gl::FLOAT, false, stride, 0);
+        gl.enable_vertex_attrib_array(1);
+        gl.vertex_attrib_pointer_with_i32(1, 2, Gl::FLOAT, false, stride, stride / 2);
+
+        gl.enable(Gl::BLEND);
+        gl.blend_func_separate(
+            Gl::SRC_ALPHA,
+            Gl::ONE_MINUS_SRC_ALPHA,
+            Gl::ONE,
+            Gl::ONE_MINUS_SRC_ALPHA,
+        );
+
+        let text_canvas = browser::document()?
+            .create_element("canvas")
+            .map_err(|err| anyhow!("Could not create the text canvas {:#?}", err))?
+            .dyn_into::<HtmlCanvasElement>()
+            .map_err(|element| anyhow!("Error converting {:#?} to HtmlCanvasElement", element))?;
+        let text_context = text_canvas
+            .get_context("2d")
+            .map_err(|err| anyhow!("Error getting the text context {:#?}", err))?
+            .ok_or_else(|| anyhow!("No 2d context for the text canvas"))?
+            .dyn_into::<CanvasRenderingContext2d>()
+            .map_err(|element| {
+                anyhow!(
+                    "Error converting {:#?} to CanvasRenderingContext2d",
+                    element
+                )
+            })?;
+
+        let renderer = WebGlRenderer {
+            gl,
+            program,
+            vertex_array,
+            buffer,
+            textures: RefCell::new(HashMap::new()),
+            text_canvas,
+            text_context,
+            batch: RefCell::new(SpriteBatch::default()),
+            viewport: Cell::new(Viewport::fit((1, 1), (1.0, 1.0), 1.0)),
+            camera: Cell::new(Point::default()),
+        };
+        let red = renderer.create_texture()?;
+        renderer
+            .gl
+            .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+                Gl::TEXTURE_2D,
+                0,
+                Gl::RGBA as i32,
+                1,
+                1,
+                0,
+                Gl::RGBA,
+                Gl::UNSIGNED_BYTE,
+                Some(&[255, 0, 0, 255]),
+            )
+            .map_err(|err| anyhow!("Could not upload the rect texture {:#?}", err))?;
+        renderer.textures.borrow_mut().insert(
+            RECT_TEXTURE.to_string(),
+            Texture {
+                texture: red,
+                size: (1, 1),
+            },
+        );
+        Ok(renderer)
+    }
+
+    fn to_screen(&self, position: &Point) -> Point {
+        let camera = self.camera.get();
+        Point {
+            x: position.x - camera.x,
+            y: position.y - camera.y,
+        }
+    }
+
+    fn scissor(&self, rect: &Rect) {
+        let viewport = self.viewport.get();
+        let left = (f64::from(rect.x()) * viewport.scale + viewport.x).round() as i32;
+        let top = (f64::from(rect.y()) * viewport.scale + viewport.y).round() as i32;
+        let right = (f64::from(rect.right()) * viewport.scale + viewport.x).round() as i32;
+        let bottom = (f64::from(rect.bottom()) * viewport.scale + viewport.y).round() as i32;
+        self.gl.scissor(
+            left,
+            viewport.height as i32 - bottom,
+            right - left,
+            bottom - top,
+        );
+    }
+
+    fn scissor_to_game(&self) {
+        let (width, height) = self.viewport.get().resolution;
+        self.scissor(&Rect::new_from_x_y(0, 0, width, height));
+    }
+
+    fn create_texture(&self) -> Result<WebGlTexture> {
+        let texture = self
+            .gl
+            .create_texture()
+            .ok_or_else(|| anyhow!("Could not create a texture"))?;
+        self.gl.bind_texture(Gl::TEXTURE_2D, Some(&texture));
+        for parameter in [Gl::TEXTURE_WRAP_S, Gl::TEXTURE_WRAP_T] {
+            self.gl
+                .tex_parameteri(Gl::TEXTURE_2D, parameter, Gl::CLAMP_TO_EDGE as i32);
+        }
+        for parameter in [Gl::TEXTURE_MIN_FILTER, Gl::TEXTURE_MAG_FILTER] {
+            self.gl
+                .tex_parameteri(Gl::TEXTURE_2D, parameter, Gl::LINEAR as i32);
+        }
+        Ok(texture)
+    }
+
+    /// Images that haven't loaded yet are skipped rather than cached empty.
+    fn texture_for(&self, image: &HtmlImageElement) -> Option<String> {
+        let key = image.src();
+        if self.textures.borrow().contains_key(&key) {
+            return Some(key);
+        }
+        if !image.complete() || image.natural_width() == 0 {
+            return None;
+        }
+
+        let uploaded = self.create_texture().and_then(|texture| {
+            self.gl
+                .tex_image_2d_with_u32_and_u32_and_html_image_element(
+                    Gl::TEXTURE_2D,
+                    0,
+                    Gl::RGBA as i32,
+                    Gl::RGBA,
+                    Gl::UNSIGNED_BYTE,
+                    image,
+                )
+                .map_err(|err| anyhow!("Could not upload {} {:#?}", key, err))?;
+            Ok(texture)
+        });
+        match uploaded {
+            Ok(texture) => {
+                let size = (image.natural_width(), image.natural_height());
+                self.textures
+                    .borrow_mut()
+                    .insert(key.clone(), Texture { texture, size });
+                Some(key)
+            }
+            Err(err) => {
+                error!("Could not create texture {:#?}", err);
+                None
+            }
+        }
+    }
+
+    fn push(&self, texture: &str, frame: &Rect, destination: &Rect) {
+        let Some(size) = self
+            .textures
+            .borrow()
+            .get(texture)
+            .map(|texture| texture.size)
+        else {
+            return;
+        };
+        let destination = Rect {
+            position: self.to_screen(&destination.position),
+            ..*destination
+        };
+        let quad = quad(&self.viewport.get(), size, frame, &destination);
+        let full = self.batch.borrow_mut().push(texture, quad);
+        if let Some((texture, vertices)) = full {
+            self.draw_batch(&texture, &vertices);
+        }
+    }
+
+    fn flush(&self) {
+        let batched = self.batch.borrow_mut().take();
+        if let Some((texture, vertices)) = batched {
+            self.draw_batch(&texture, &vertices);
+        }
+    }
+
+    fn draw_batch(&self, texture: &str, vertices: &[f32]) {
+        let textures = self.textures.borrow();
+        let Some(Texture { texture, .. }) = textures.get(texture) else {
+            return;
+        };
+        self.gl.use_program(Some(&self.program));
+        self.gl.bind_vertex_array(Some(&self.vertex_array));
+        self.gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&self.buffer));
+        self.gl.buffer_data_with_array_buffer_view(
+            Gl::ARRAY_BUFFER,
+            &js_sys::Float32Array::from(vertices),
+            Gl::STREAM_DRAW,
+        );
+        self.gl.active_texture(Gl::TEXTURE0);
+        self.gl.bind_texture(Gl::TEXTURE_2D, Some(texture));
+        self.gl.draw_arrays(
+            Gl::TRIANGLES,
+            0,
+            (vertices.len() / FLOATS_PER_VERTEX) as i32,
+        );
+    }
+}
+
+impl Renderer for WebGlRenderer {
+    fn resize(&self, viewport: &Viewport) -> Result<()> {
+        let canvas = self
+            .gl
+            .canvas()
+            .ok_or_else(|| anyhow!("No canvas for the WebGL context"))?
+            .dyn_into::<HtmlCanvasElement>()
+            .map_err(|element| anyhow!("Error converting {:#?} to HtmlCanvasElement", element))?;
+        canvas.set_width(viewport.width);
+        canvas.set_height(viewport.height);
+        self.gl
+            .viewport(0, 0, viewport.width as i32, viewport.height as i32);
+        self.viewport.set(*viewport);
+        self.gl.enable(Gl::SCISSOR_TEST);
+        self.scissor_to_game();
+
+        self.text_canvas
+            .set_width((f64::from(TEXT_WIDTH) * viewport.scale).ceil() as u32);
+        self.text_canvas
+            .set_height((f64::from(TEXT_HEIGHT) * viewport.scale).ceil() as u32);
+        self.text_context
+            .set_transform(viewport.scale, 0.0, 0.0, viewport.scale, 0.0, 0.0)
+            .map_err(|e| anyhow!("Error scaling the text canvas {:#?}", e))
+    }
+
+    fn set_camera(&self, camera: &Camera) {
+        self.camera.set(camera.offset());
+    }
+
+    fn reset_camera(&self) {
+        self.camera.set(Point::default());
+    }
+
+    fn clear(&self, rect: &Rect) {
+        self.flush();
+        self.scissor(rect);
+        self.gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        self.gl.clear(Gl::COLOR_BUFFER_BIT);
+        self.scissor_to_game();
+    }
+
+    fn draw_image(&self, image: &dyn ImageSource, frame: &Rect, destination: &Rect) {
+        let Some(texture) = html_image(image).and_then(|image| self.texture_for(image)) else {
+            return;
+        };
+        self.push(&texture, frame, destination);
+    }
+
+    fn draw_entire_image(&self, image: &dyn ImageSource, position: &Point) {
+        let Some(image) = html_image(image) else {
+            return;
+        };
+        let Some(texture) = self.texture_for(image) else {
+            return;
+        };
+        let width = image.natural_width() as i16;
+        let height = image.natural_height() as i16;
+        self.push(
+            &texture,
+            &Rect::new_from_x_y(0, 0, width, height),
+            &Rect::new(*position, width, height),
+        );
+    }
+
+    fn draw_rect(&self, bounding_box: &Rect) {
+        let texel = Rect::new_from_x_y(0, 0, 1, 1);
+        let (x, y) = (bounding_box.x(), bounding_box.y());
+        let (width, height) = (bounding_box.width, bounding_box.height);
+        for edge in [
+            Rect::new_from_x_y(x, y, width, 1),
+            Rect::new_from_x_y(x, bounding_box.bottom() - 1, width, 1),
+            Rect::new_from_x_y(x, y, 1, height),
+            Rect::new_from_x_y(bounding_box.right() - 1, y, 1, height),
+        ] {
+            self.push(RECT_TEXTURE, &texel, &edge);
+        }
+    }
+
+    fn draw_text(&self, text: &str, location: &Point) -> Result<()> {
+        self.text_context
+            .clear_rect(0.0, 0.0, TEXT_WIDTH.into(), TEXT_HEIGHT.into());
+        self.text_context.set_font("16pt serif");
+        self.text_context
+            .fill_text(text, 0.0, TEXT_BASELINE.into())
+            .map_err(|e| anyhow!("Error filling text {:#?}", e))?;
+
+        if !self.textures.borrow().contains_key(TEXT_TEXTURE) {
+            let texture = self.create_texture()?;
+            self.textures.borrow_mut().insert(
+                TEXT_TEXTURE.to_string(),
+                Texture {
+                    texture,
+                    size: (0, 0),
+                },
+            );
+        }
+        let size = (self.text_canvas.width(), self.text_canvas.height());
+        if let Some(text) = self.textures.borrow_mut().get_mut(TEXT_TEXTURE) {
+            self.gl.bind_texture(Gl::TEXTURE_2D, Some(&text.texture));
+            self.gl
+                .tex_image_2d_with_u32_and_u32_and_html_canvas_element(
+                    Gl::TEXTURE_2D,
+                    0,
+                    Gl::RGBA as i32,
+                    Gl::RGBA,
+                    Gl::UNSIGNED_BYTE,
+                    &self.text_canvas,
+                )
+                .map_err(|e| anyhow!("Error uploading text {:#?}", e))?;
+            text.size = size;
+        }
+
+        let texture_size = (size.0 as i16, size.1 as i16);
+        self.push(
+            TEXT_TEXTURE,
+            &Rect::new_from_x_y(0, 0, texture_size.0, texture_size.1),
+            &Rect::new_from_x_y(
+                location.x,
+                location.y - TEXT_BASELINE,
+                TEXT_WIDTH,
+                TEXT_HEIGHT,
+            ),
+        );
+        // The text canvas is redrawn for the next call, so draw this one now.
+        self.flush();
+        Ok(())
+    }
+
+    fn present(&self) {
+        self.flush();
+    }
+}
+
+fn compile_shader(gl: &Gl, kind: u32, source: &str) -> Result<WebGlShader> {
+    let shader = gl
+        .create_shader(kind)
+        .ok_or_else(|| anyhow!("Could not create a shader"))?;
+    gl.shader_source(&shader, source);
+    gl.compile_shader(&shader);
+    if gl
+        .get_shader_parameter(&shader, Gl::COMPILE_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(shader)
+    } else {
+        Err(anyhow!(
+            "Error compiling shader {}",
+            gl.get_shader_info_log(&shader).unwrap_or_default()
+        ))
+    }
+}
+
+fn link_program(gl: &Gl) -> Result<WebGlProgram> {
+    let vertex_shader = compile_shader(gl, Gl::VERTEX_SHADER, VERTEX_SHADER)?;
+    let fragment_shader = compile_shader(gl, Gl::FRAGMENT_SHADER, FRAGMENT_SHADER)?;
+    let program = gl
+        .create_program()
+        .ok_or_else(|| anyhow!("Could not create a shader program"))?;
+    gl.attach_shader(&program, &vertex_shader);
+    gl.attach_shader(&program, &fragment_shader);
+    gl.bind_attrib_location(&program, 0, "position");
+    gl.bind_attrib_location(&program, 1, "uv");
+    gl.link_program(&program);
+    if gl
+        .get_program_parameter(&program, Gl::LINK_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(program)
+    } else {
+        Err(anyhow!(
+            "Error linking shader program {}",
+            gl.get_program_info_log(&program).unwrap_or_default()
+        ))
+    }
+}
+
+struct Texture {
+    texture: WebGlTexture,
+    size: (u32, u32),
+}
+
+#[derive(Default)]
+struct SpriteBatch {
+    texture: Option<String>,
+    vertices: Vec<f32>,
+}
+
+impl SpriteBatch {
+    /// A quad from another texture hands back the batch so far to be drawn.
+    fn push(&mut self, texture: &str, quad: [f32; FLOATS_PER_QUAD]) -> Option<(String, Vec<f32>)> {
+        let full = match &self.texture {
+            Some(current) if current != texture => self.take(),
+            _ => None,
+        };
+        self.texture = Some(texture.to_string());
+        self.vertices.extend_from_slice(&quad);
+        full
+    }
+
+    fn take(&mut self) -> Option<(String, Vec<f32>)> {
+        let texture = self.texture.take()?;
+        Some((texture, std::mem::take(&mut self.vertices)))
+    }
+}
+
+fn quad(
+    viewport: &Viewport,
+    texture_size: (u32, u32),
+    frame: &Rect,
+    destination: &Rect,
+) -> [f32; FLOATS_PER_QUAD] {
+    let clip_x = |x: i16| {
+        ((f64::from(x) * viewport.scale + viewport.x) / f64::from(viewport.width) * 2.0 - 1.0)
+            as f32
+    };
+    let clip_y = |y: i16| {
+        (1.0 - (f64::from(y) * viewport.scale + viewport.y) / f64::from(viewport.height) * 2.0)
+            as f32
+    };
+    let u = |x: i16| f32::from(x) / texture_size.0.max(1) as f32;
+    let v = |y: i16| f32::from(y) / texture_size.1.max(1) as f32;
+
+    let (left, right) = (clip_x(destination.x()), clip_x(destination.right()));
+    let (top, bottom) = (clip_y(destination.y()), clip_y(destination.bottom()));
+    let (u0, u1) = (u(frame.x()), u(frame.right()));
+    let (v0, v1) = (v(frame.y()), v(frame.bottom()));
+    [
+        left, top, u0, v0, right, top, u1, v0, left, bottom, u0, v1, //
+        left, bottom, u0, v1, right, top, u1, v0, right, bottom, u1, v1,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_full_screen_quad_covers_clip_space() {
+        let viewport = Viewport::fit((600, 600), (600.0, 600.0), 1.0);
+
+        let quad = quad(
+            &viewport,
+            (200, 100),
+            &Rect::new_from_x_y(100, 50, 100, 50),
+            &Rect::new_from_x_y(0, 0, 600, 600),
+        );
+
+        assert_eq!(&quad[0..4], &[-1.0, 1.0, 0.5, 0.5]);
+        assert_eq!(&quad[20..24], &[1.0, -1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn quads_are_placed_inside_the_letterbox() {
+        let viewport = Viewport::fit((600, 600), (1200.0, 600.0), 1.0);
+
+        let quad = quad(
+            &viewport,
+            (1, 1),
+            &Rect::new_from_x_y(0, 0, 1, 1),
+            &Rect::new_from_x_y(0, 0, 600, 600),
+        );
+
+        assert_eq!((quad[0], quad[1]), (-0.5, 1.0));
+        assert_eq!((quad[20], quad[21]), (0.5, -1.0));
+    }
+
+    #[test]
+    fn sprites_from_one_texture_share_a_batch() {
+        let mut batch = SpriteBatch::default();
+
+        assert!(batch.push("rhb.png", [0.0; FLOATS_PER_QUAD]).is_none());
+        assert!(batch.push("rhb.png", [0.0; FLOATS_PER_QUAD]).is_none());
+        let (texture, vertices) = batch.push("tiles.png", [0.0; FLOATS_PER_QUAD]).unwrap();
+
+        assert_eq!(texture, "rhb.png");
+        assert_eq!(vertices.len(), FLOATS_PER_QUAD * 2);
+        let (texture, vertices) = batch.take().unwrap();
+        assert_eq!(texture, "tiles.png");
+        assert_eq!(vertices.len(), FLOATS_PER_QUAD);
+        assert!(batch.take().is_none());
+    }
+}
@@ -0,0 +1,208 @@
+use super::KeyState;
+
+use serde::{Deserialize, Serialize};
+
+const MAX_RECORDED_KEYS: usize = u32::BITS as usize;
+
+/// Key state per tick, run-length encoded.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Recording {
+    pub seed: u64,
+    /// Older recordings don't have bindings.
+    #[serde(default)]
+    pub bindings: Option<String>,
+    pub keys: Vec<String>,
+    pub ticks: Vec<(TickInput, u32)>,
+}
+
+/// A press and release within one tick survives a replay.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TickInput {
+    pub held: u32,
+    pub pressed: u32,
+    pub released: u32,
+}
+
+pub struct InputRecorder {
+    recording: Recording,
+}
+
+impl InputRecorder {
+    pub fn new(seed: u64, bindings: Option<String>) -> Self {
+        InputRecorder {
+            recording: Recording {
+                seed,
+                bindings,
+                ..Recording::default()
+            },
+        }
+    }
+
+    pub fn record(&mut self, keystate: &KeyState) {
+        let tick = TickInput {
+            held: self.mask(keystate.pressed_keys()),
+            pressed: self.mask(keystate.just_pressed_keys()),
+            released: self.mask(keystate.just_released_keys()),
+        };
+
+        match self.recording.ticks.last_mut() {
+            Some((last, count)) if *last == tick => *count += 1,
+            _ => self.recording.ticks.push((tick, 1)),
+        }
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    fn mask<'a>(&mut self, codes: impl Iterator<Item = &'a str>) -> u32 {
+        let mut codes: Vec<&str> = codes.collect();
+        codes.sort_unstable();
+        codes
+            .into_iter()
+            .filter_map(|code| self.key_index(code))
+            .fold(0, |mask, index| mask | (1 << index))
+    }
+
+    // Keys past the 32nd distinct code are not recorded.
+    fn key_index(&mut self, code: &str) -> Option<usize> {
+        let keys = &mut self.recording.keys;
+        match keys.iter().position(|key| key == code) {
+            Some(index) => Some(index),
+            None if keys.len() < MAX_RECORDED_KEYS => {
+                keys.push(code.into());
+                Some(keys.len() - 1)
+            }
+            None => None,
+        }
+    }
+}
+
+pub struct Replay {
+    recording: Recording,
+    span: usize,
+    tick_in_span: u32,
+}
+
+impl Replay {
+    pub fn new(recording: Recording) -> Self {
+        Replay {
+            recording,
+            span: 0,
+            tick_in_span: 0,
+        }
+    }
+
+    /// Returns false once the recording has run out.
+    pub fn next_tick(&mut self, keystate: &mut KeyState) -> bool {
+        let Some((tick, count)) = self.recording.ticks.get(self.span).copied() else {
+            return false;
+        };
+
+        self.tick_in_span += 1;
+        if self.tick_in_span >= count {
+            self.span += 1;
+            self.tick_in_span = 0;
+        }
+
+        keystate.start_tick();
+        let unrecorded: Vec<String> = keystate
+            .pressed_keys()
+            .filter(|code| !self.recording.keys.iter().any(|key| key == code))
+            .map(String::from)
+            .collect();
+        unrecorded
+            .iter()
+            .for_each(|code| keystate.set_released(code));
+        for (index, code) in self.recording.keys.iter().enumerate() {
+            let bit = 1 << index;
+            let (held, pressed, released) = (
+                tick.held & bit != 0,
+                tick.pressed & bit != 0,
+                tick.released & bit != 0,
+            );
+            if held {
+                // Released while held means it was let go and pressed again.
+                if released {
+                    keystate.set_pressed(code);
+                    keystate.set_released(code);
+                }
+                keystate.set_pressed(code);
+            } else {
+                if pressed {
+                    keystate.set_pressed(code);
+                }
+                keystate.set_released(code);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(codes: &[&str]) -> KeyState {
+        let mut keystate = KeyState::new();
+        codes.iter().for_each(|code| keystate.set_pressed(code));
+        keystate
+    }
+
+    #[test]
+    fn recording_is_run_length_encoded_and_replays_every_tick() {
+        let ticks = [
+            keys(&[]),
+            keys(&["ArrowRight"]),
+            keys(&["ArrowRight"]),
+            keys(&["ArrowRight"]),
+            keys(&["ArrowRight", "Space"]),
+            keys(&["ArrowRight"]),
+        ];
+        let mut recorder = InputRecorder::new(7, None);
+        let mut keystate = KeyState::new();
+        for tick in &ticks {
+            keystate.update_pressed(tick.pressed_keys());
+            recorder.record(&keystate);
+        }
+
+        let recording = recorder.recording().clone();
+        let counts: Vec<u32> = recording.ticks.iter().map(|(_, count)| *count).collect();
+        assert_eq!(counts, vec![1, 1, 2, 1, 1]);
+
+        let json = serde_json::to_string(&recording).unwrap();
+        let mut replay = Replay::new(serde_json::from_str(&json).unwrap());
+        let mut replayed = KeyState::new();
+        for expected in &ticks {
+            assert!(replay.next_tick(&mut replayed));
+            assert_eq!(
+                replayed.is_pressed("ArrowRight"),
+                expected.is_pressed("ArrowRight")
+            );
+            assert_eq!(replayed.is_pressed("Space"), expected.is_pressed("Space"));
+        }
+        assert!(!replay.next_tick(&mut replayed));
+    }
+
+    #[test]
+    fn a_tap_within_one_tick_is_replayed() {
+        let mut recorder = InputRecorder::new(7, None);
+        let mut keystate = KeyState::new();
+        keystate.start_tick();
+        keystate.set_pressed("Space");
+        keystate.set_released("Space");
+        recorder.record(&keystate);
+        keystate.start_tick();
+        recorder.record(&keystate);
+
+        let mut replay = Replay::new(recorder.recording().clone());
+        let mut replayed = KeyState::new();
+        replay.next_tick(&mut replayed);
+        assert!(replayed.just_pressed("Space"));
+        assert!(replayed.just_released("Space"));
+        assert!(!replayed.is_pressed("Space"));
+
+        replay.next_tick(&mut replayed);
+        assert!(!replayed.just_pressed("Space"));
+    }
+}
@@ -0,0 +1,18 @@
+use crate::browser;
+use anyhow::Result;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+use wasm_bindgen::JsCast;
+
+pub fn add_resize_handler() -> Result<UnboundedReceiver<()>> {
+    let window = browser::window()?;
+    let (mut resize_sender, resize_receiver) = unbounded();
+    let on_resize = browser::closure_wrap(Box::new(move || {
+        if let Err(err) = resize_sender.start_send(()) {
+            error!("Could not send resize message {:#?}", err);
+        }
+    }) as Box<dyn FnMut()>);
+    window.set_onresize(Some(on_resize.as_ref().unchecked_ref()));
+    on_resize.forget();
+
+    Ok(resize_receiver)
+}
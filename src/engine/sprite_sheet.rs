@@ -1,22 +1,29 @@
-use web_sys::HtmlImageElement;
+use super::{load_json, Cell, ImageElement, Platform, Rect, Renderer, Sheet};
 
-use super::{Cell, Rect, Renderer, Sheet};
+use anyhow::Result;
 
 pub(crate) struct SpriteSheet {
     sheet: Sheet,
-    image: HtmlImageElement,
+    image: ImageElement,
 }
 
 impl SpriteSheet {
-    pub(crate) fn new(sheet: Sheet, image: HtmlImageElement) -> Self {
+    pub(crate) fn new(sheet: Sheet, image: ImageElement) -> Self {
         Self { sheet, image }
     }
 
+    pub(crate) async fn load(platform: &dyn Platform, name: &str) -> Result<Self> {
+        Ok(Self::new(
+            load_json::<Sheet>(platform, &format!("{}.json", name)).await?,
+            platform.load_image(&format!("{}.png", name)).await?,
+        ))
+    }
+
     pub(crate) fn cell(&self, name: &str) -> Option<&Cell> {
         self.sheet.frames.get(name)
     }
 
-    pub(crate) fn draw(&self, renderer: &Renderer, source: &Rect, destination: &Rect) {
-        renderer.draw_image(&self.image, source, destination);
+    pub(crate) fn draw(&self, renderer: &dyn Renderer, source: &Rect, destination: &Rect) {
+        renderer.draw_image(self.image.as_ref(), source, destination);
     }
 }
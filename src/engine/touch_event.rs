@@ -0,0 +1,203 @@
+use crate::browser;
+
+use anyhow::Result;
+use futures::channel::mpsc::UnboundedSender;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use wasm_bindgen::JsCast;
+use web_sys::PointerEvent;
+
+use super::KeyPress;
+
+/// Touch gestures show up in the `KeyState` under these codes.
+pub const TOUCH_PREFIX: &str = "Touch";
+pub const TOUCH_TAP: &str = "TouchTap";
+pub const TOUCH_SWIPE_DOWN: &str = "TouchSwipeDown";
+const SWIPE_DISTANCE: i32 = 40;
+
+struct Pointer {
+    start_x: i32,
+    start_y: i32,
+    swiping: bool,
+}
+
+/// Pointers are tracked separately, so one can tap while another swipes.
+#[derive(Default)]
+pub struct Gestures {
+    pointers: HashMap<i32, Pointer>,
+}
+
+impl Gestures {
+    pub fn pointer_down(&mut self, id: i32, x: i32, y: i32) -> Vec<KeyPress> {
+        self.pointers.insert(
+            id,
+            Pointer {
+                start_x: x,
+                start_y: y,
+                swiping: false,
+            },
+        );
+        vec![]
+    }
+
+    pub fn pointer_move(&mut self, id: i32, x: i32, y: i32) -> Vec<KeyPress> {
+        let already_swiping = self.swiping();
+        let Some(pointer) = self.pointers.get_mut(&id) else {
+            return vec![];
+        };
+
+        let dx = x - pointer.start_x;
+        let dy = y - pointer.start_y;
+        if pointer.swiping || dy < SWIPE_DISTANCE || dy < dx.abs() {
+            return vec![];
+        }
+
+        pointer.swiping = true;
+        if already_swiping {
+            vec![]
+        } else {
+            vec![KeyPress::KeyDown(TOUCH_SWIPE_DOWN.into())]
+        }
+    }
+
+    pub fn pointer_up(&mut self, id: i32, x: i32, y: i32) -> Vec<KeyPress> {
+        let Some(pointer) = self.pointers.remove(&id) else {
+            return vec![];
+        };
+
+        if pointer.swiping {
+            self.end_swipe()
+        } else if (x - pointer.start_x).abs() < SWIPE_DISTANCE
+            && (y - pointer.start_y).abs() < SWIPE_DISTANCE
+        {
+            vec![
+                KeyPress::KeyDown(TOUCH_TAP.into()),
+                KeyPress::KeyUp(TOUCH_TAP.into()),
+            ]
+        } else {
+            vec![]
+        }
+    }
+
+    pub fn pointer_cancel(&mut self, id: i32) -> Vec<KeyPress> {
+        match self.pointers.remove(&id) {
+            Some(pointer) if pointer.swiping => self.end_swipe(),
+            _ => vec![],
+        }
+    }
+
+    fn swiping(&self) -> bool {
+        self.pointers.values().any(|pointer| pointer.swiping)
+    }
+
+    fn end_swipe(&self) -> Vec<KeyPress> {
+        if self.swiping() {
+            vec![]
+        } else {
+            vec![KeyPress::KeyUp(TOUCH_SWIPE_DOWN.into())]
+        }
+    }
+}
+
+type KeyPressSender = Rc<RefCell<UnboundedSender<KeyPress>>>;
+
+pub(super) fn add_touch_handlers(sender: KeyPressSender) -> Result<()> {
+    let canvas = browser::canvas()?;
+    let gestures = Rc::new(RefCell::new(Gestures::default()));
+
+    let handler = |recognize: fn(&mut Gestures, &PointerEvent) -> Vec<KeyPress>| {
+        let sender = sender.clone();
+        let gestures = gestures.clone();
+        browser::closure_wrap(Box::new(move |event: PointerEvent| {
+            event.prevent_default();
+            for key_press in recognize(&mut gestures.borrow_mut(), &event) {
+                if let Err(err) = sender.borrow_mut().start_send(key_press) {
+                    error!("Could not send touch message {:#?}", err);
+                }
+            }
+        }) as Box<dyn FnMut(PointerEvent)>)
+    };
+
+    let on_down = handler(|gestures, event| {
+        gestures.pointer_down(event.pointer_id(), event.client_x(), event.client_y())
+    });
+    let on_move = handler(|gestures, event| {
+        gestures.pointer_move(event.pointer_id(), event.client_x(), event.client_y())
+    });
+    let on_up = handler(|gestures, event| {
+        gestures.pointer_up(event.pointer_id(), event.client_x(), event.client_y())
+    });
+    let on_cancel = handler(|gestures, event| gestures.pointer_cancel(event.pointer_id()));
+
+    canvas.set_onpointerdown(Some(on_down.as_ref().unchecked_ref()));
+    canvas.set_onpointermove(Some(on_move.as_ref().unchecked_ref()));
+    canvas.set_onpointerup(Some(on_up.as_ref().unchecked_ref()));
+    canvas.set_onpointercancel(Some(on_cancel.as_ref().unchecked_ref()));
+    on_down.forget();
+    on_move.forget();
+    on_up.forget();
+    on_cancel.forget();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::KeyState;
+
+    fn apply(state: &mut KeyState, key_presses: Vec<KeyPress>) {
+        for key_press in key_presses {
+            match key_press {
+                KeyPress::KeyDown(code) => state.set_pressed(&code),
+                KeyPress::KeyUp(code) => state.set_released(&code),
+            }
+        }
+    }
+
+    #[test]
+    fn a_short_touch_is_a_tap() {
+        let mut gestures = Gestures::default();
+        let mut state = KeyState::new();
+
+        apply(&mut state, gestures.pointer_down(1, 100, 100));
+        apply(&mut state, gestures.pointer_move(1, 105, 110));
+        apply(&mut state, gestures.pointer_up(1, 105, 110));
+
+        assert!(state.just_pressed(TOUCH_TAP));
+        assert!(!state.is_pressed(TOUCH_TAP));
+        assert!(!state.just_pressed(TOUCH_SWIPE_DOWN));
+    }
+
+    #[test]
+    fn swiping_down_holds_until_the_finger_lifts() {
+        let mut gestures = Gestures::default();
+        let mut state = KeyState::new();
+
+        apply(&mut state, gestures.pointer_down(1, 100, 100));
+        apply(&mut state, gestures.pointer_move(1, 100, 160));
+        assert!(state.is_pressed(TOUCH_SWIPE_DOWN));
+
+        apply(&mut state, gestures.pointer_up(1, 100, 200));
+        assert!(!state.is_pressed(TOUCH_SWIPE_DOWN));
+        assert!(!state.just_pressed(TOUCH_TAP));
+    }
+
+    #[test]
+    fn a_second_finger_can_tap_during_a_swipe() {
+        let mut gestures = Gestures::default();
+        let mut state = KeyState::new();
+
+        apply(&mut state, gestures.pointer_down(1, 100, 100));
+        apply(&mut state, gestures.pointer_move(1, 100, 160));
+        apply(&mut state, gestures.pointer_down(2, 400, 100));
+        apply(&mut state, gestures.pointer_move(2, 400, 160));
+        apply(&mut state, gestures.pointer_down(3, 300, 300));
+        apply(&mut state, gestures.pointer_up(3, 300, 300));
+        assert!(state.just_pressed(TOUCH_TAP));
+
+        apply(&mut state, gestures.pointer_up(1, 100, 160));
+        assert!(state.is_pressed(TOUCH_SWIPE_DOWN));
+        apply(&mut state, gestures.pointer_cancel(2));
+        assert!(!state.is_pressed(TOUCH_SWIPE_DOWN));
+    }
+}
@@ -0,0 +1,83 @@
+use super::Point;
+use std::ops::{Add, AddAssign, Mul, Sub};
+
+/// A position or velocity with sub-pixel precision; round only to draw.
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct Vector {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vector {
+    pub fn round(&self) -> Point {
+        Point {
+            x: self.x.round() as i16,
+            y: self.y.round() as i16,
+        }
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, other: Vector) -> Vector {
+        Vector {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+impl AddAssign for Vector {
+    fn add_assign(&mut self, other: Vector) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, other: Vector) -> Vector {
+        Vector {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+}
+
+impl Mul<f32> for Vector {
+    type Output = Vector;
+
+    fn mul(self, factor: f32) -> Vector {
+        Vector {
+            x: self.x * factor,
+            y: self.y * factor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fractions_add_up_before_rounding() {
+        let mut position = Vector { x: 10.0, y: 0.0 };
+        let velocity = Vector { x: 0.25, y: 0.25 };
+
+        position += velocity;
+        assert_eq!(position.round(), Point { x: 10, y: 0 });
+
+        position += velocity;
+        assert_eq!(position, Vector { x: 10.5, y: 0.5 });
+        assert_eq!(position.round(), Point { x: 11, y: 1 });
+    }
+
+    #[test]
+    fn scaling_a_difference_moves_part_of_the_way() {
+        let from = Vector { x: 10.0, y: 20.0 };
+        let to = Vector { x: 20.0, y: 0.0 };
+
+        assert_eq!(from + (to - from) * 0.25, Vector { x: 12.5, y: 15.0 });
+    }
+}
@@ -0,0 +1,88 @@
+/// Scales a game uniformly to fit a high-DPI canvas, with letterbox bars.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub width: u32,
+    pub height: u32,
+    /// Nothing outside it is drawn.
+    pub resolution: (i16, i16),
+    pub scale: f64,
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Viewport {
+    /// A canvas without a size yet gets the logical resolution.
+    pub fn fit(resolution: (i16, i16), css_size: (f64, f64), device_pixel_ratio: f64) -> Self {
+        let logical_width = f64::from(resolution.0);
+        let logical_height = f64::from(resolution.1);
+        let (css_width, css_height) = if css_size.0 > 0.0 && css_size.1 > 0.0 {
+            css_size
+        } else {
+            (logical_width, logical_height)
+        };
+        let device_pixel_ratio = if device_pixel_ratio > 0.0 {
+            device_pixel_ratio
+        } else {
+            1.0
+        };
+
+        let width = (css_width * device_pixel_ratio).round();
+        let height = (css_height * device_pixel_ratio).round();
+        let scale = (width / logical_width).min(height / logical_height);
+        Viewport {
+            width: width as u32,
+            height: height as u32,
+            resolution,
+            scale,
+            x: ((width - logical_width * scale) / 2.0).floor(),
+            y: ((height - logical_height * scale) / 2.0).floor(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_matching_canvas_is_drawn_one_to_one() {
+        let viewport = Viewport::fit((600, 600), (600.0, 600.0), 1.0);
+
+        assert_eq!(
+            viewport,
+            Viewport {
+                width: 600,
+                height: 600,
+                resolution: (600, 600),
+                scale: 1.0,
+                x: 0.0,
+                y: 0.0
+            }
+        );
+    }
+
+    #[test]
+    fn high_dpi_screens_get_a_bigger_backing_store() {
+        let viewport = Viewport::fit((600, 600), (600.0, 600.0), 2.0);
+
+        assert_eq!((viewport.width, viewport.height), (1200, 1200));
+        assert_eq!(viewport.scale, 2.0);
+    }
+
+    #[test]
+    fn a_wide_canvas_is_letterboxed_at_the_sides() {
+        let viewport = Viewport::fit((600, 600), (1000.0, 500.0), 1.5);
+
+        assert_eq!((viewport.width, viewport.height), (1500, 750));
+        assert_eq!(viewport.scale, 1.25);
+        assert_eq!((viewport.x, viewport.y), (375.0, 0.0));
+    }
+
+    #[test]
+    fn a_canvas_without_a_size_falls_back_to_the_resolution() {
+        let viewport = Viewport::fit((800, 600), (0.0, 0.0), 0.0);
+
+        assert_eq!((viewport.width, viewport.height), (800, 600));
+        assert_eq!(viewport.scale, 1.0);
+    }
+}
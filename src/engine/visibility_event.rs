@@ -0,0 +1,28 @@
+use crate::browser;
+use crate::engine::Audio;
+use anyhow::Result;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+use wasm_bindgen::JsCast;
+
+/// Sends `true` when the page is hidden and `false` when it is shown. A hidden
+/// page gets no frames, so `audio` is suspended here.
+pub fn add_visibility_handler(audio: Audio) -> Result<UnboundedReceiver<bool>> {
+    let document = browser::document()?;
+    let (mut visibility_sender, visibility_receiver) = unbounded();
+    let hidden_document = document.clone();
+    let on_visibility_change = browser::closure_wrap(Box::new(move || {
+        let hidden = hidden_document.hidden();
+        if hidden {
+            if let Err(err) = audio.suspend() {
+                error!("Could not suspend audio {:#?}", err);
+            }
+        }
+        if let Err(err) = visibility_sender.start_send(hidden) {
+            error!("Could not send visibility message {:#?}", err);
+        }
+    }) as Box<dyn FnMut()>);
+    document.set_onvisibilitychange(Some(on_visibility_change.as_ref().unchecked_ref()));
+    on_visibility_change.forget();
+
+    Ok(visibility_receiver)
+}
@@ -6,6 +6,7 @@ mod sound;
 mod walk_the_dog;
 
 use anyhow::Result;
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen(start)]
@@ -13,11 +14,42 @@ pub fn main_js() -> Result<(), JsValue> {
     console_error_panic_hook::set_once();
 
     browser::spawn_local(async move {
-        let game = walk_the_dog::WalkTheDog::new();
-        engine::GameLoop::start(game)
+        let platform = Rc::new(engine::WebPlatform);
+        let replay = load_replay(platform.as_ref())
             .await
-            .expect("Coulid not start geame");
+            .expect("Could not load replay");
+        let game = match &replay {
+            Some(recording) => walk_the_dog::WalkTheDog::replay(platform.clone(), recording),
+            None => walk_the_dog::WalkTheDog::new(
+                platform.clone(),
+                browser::query_param("seed")
+                    .ok()
+                    .flatten()
+                    .and_then(|seed| seed.parse().ok()),
+            ),
+        };
+        let config = engine::GameLoopConfig {
+            renderer: match browser::query_param("renderer").ok().flatten().as_deref() {
+                Some("webgl") => engine::RendererBackend::WebGl,
+                _ => engine::RendererBackend::Canvas,
+            },
+            ..engine::GameLoopConfig::default()
+        };
+        match replay {
+            Some(recording) => {
+                engine::GameLoop::replay(game, platform.as_ref(), recording, config).await
+            }
+            None => engine::GameLoop::start(game, platform.as_ref(), config).await,
+        }
+        .expect("Coulid not start geame");
     });
 
     Ok(())
 }
+
+async fn load_replay(platform: &dyn engine::Platform) -> Result<Option<engine::Recording>> {
+    match browser::query_param("replay")? {
+        Some(replay_path) => Ok(Some(engine::load_json(platform, &replay_path).await?)),
+        None => Ok(None),
+    }
+}
@@ -1,105 +1,658 @@
-use std::rc::Rc;
-
-use web_sys::HtmlImageElement;
-
-use crate::engine::{Image, Point, Rect, SpriteSheet};
-use crate::walk_the_dog::{Barrier, Obstacle, Platform};
-
-const LOW_PLATFORM: i16 = 420;
-const HIGH_PLATFORM: i16 = 375;
-const FIRST_PLATFORM: i16 = 370;
-
-const STONE_ON_GROUND: i16 = 546;
-
-const FLOATING_PLATFORM_SPRITES: [&str; 3] = ["13.png", "14.png", "15.png"];
-const PLATFORM_WIDTH: i16 = 384;
-const PLATFORM_HEIGHT: i16 = 93;
-const PLATFORM_EDGE_WIDTH: i16 = 60;
-const PLATFORM_EDGE_HEIGHT: i16 = 54;
-const FLOATING_PLATFORM_BOUNDING_BOXES: [Rect; 3] = [
-    Rect::new_from_x_y(0, 0, PLATFORM_EDGE_WIDTH, PLATFORM_EDGE_HEIGHT),
-    Rect::new_from_x_y(
-        PLATFORM_EDGE_WIDTH,
-        0,
-        PLATFORM_WIDTH - (PLATFORM_EDGE_WIDTH * 2),
-        PLATFORM_HEIGHT,
-    ),
-    Rect::new_from_x_y(
-        PLATFORM_WIDTH - PLATFORM_EDGE_WIDTH,
-        0,
-        PLATFORM_EDGE_WIDTH,
-        PLATFORM_EDGE_HEIGHT,
-    ),
-];
-
-fn create_floating_platform(sprite_sheet: Rc<SpriteSheet>, position: Point) -> Platform {
-    Platform::new(
-        sprite_sheet,
-        position,
-        &FLOATING_PLATFORM_SPRITES,
-        &FLOATING_PLATFORM_BOUNDING_BOXES,
-    )
+use std::{collections::HashMap, rc::Rc};
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+use crate::engine::{
+    self, CollisionShapes, Image, ImageElement, Point, Rect, Shape, SheetRect, SpriteSheet,
+};
+use crate::walk_the_dog::{
+    Barrier, Coin, Collectible, DifficultyCurve, Enemy, Movement, Obstacle, Platform, PowerUp,
+    PowerUpKind,
+};
+
+#[derive(Deserialize, Clone)]
+pub(crate) struct SegmentFile {
+    pub(crate) start: String,
+    pub(crate) course: Vec<CourseEntry>,
+    pub(crate) segments: Vec<SegmentDefinition>,
+    #[serde(default)]
+    pub(crate) difficulty: DifficultyCurve,
+    #[serde(default)]
+    pub(crate) shapes: CollisionShapes,
 }
 
-pub(crate) fn stone_and_platform(
-    stone: HtmlImageElement,
-    sprite_sheet: Rc<SpriteSheet>,
-    offset_x: i16,
-) -> Vec<Box<dyn Obstacle>> {
-    const INITIAL_STONE_OFFSET: i16 = 150;
+/// A segment that can be picked at random, rated from 0 (easy) to 1 (hard).
+#[derive(Deserialize, Clone)]
+pub(crate) struct CourseEntry {
+    pub(crate) name: String,
+    pub(crate) difficulty: f32,
+}
 
-    vec![
-        Box::new(Barrier::new(Image::new(
-            stone,
-            Point {
-                x: offset_x + INITIAL_STONE_OFFSET,
-                y: STONE_ON_GROUND,
-            },
-        ))),
-        Box::new(create_floating_platform(
-            sprite_sheet,
-            Point {
-                x: offset_x + FIRST_PLATFORM,
-                y: LOW_PLATFORM,
-            },
-        )),
-    ]
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SegmentDefinition {
+    pub(crate) name: String,
+    pub(crate) obstacles: Vec<ObstacleDefinition>,
+    #[serde(default)]
+    pub(crate) power_ups: Vec<PowerUpDefinition>,
+    #[serde(default)]
+    pub(crate) coins: Vec<CoinDefinition>,
+}
+
+/// One obstacle in a segment. `x` is relative to the start of the segment,
+/// `y` is an absolute canvas coordinate.
+#[derive(Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum ObstacleDefinition {
+    Barrier {
+        image: String,
+        x: i16,
+        y: i16,
+    },
+    #[serde(rename_all = "camelCase")]
+    Platform {
+        sprites: Vec<String>,
+        x: i16,
+        y: i16,
+        bounding_boxes: Vec<SheetRect>,
+    },
+    Enemy {
+        sprite: String,
+        x: i16,
+        y: i16,
+        #[serde(flatten)]
+        movement: Movement,
+    },
+}
+
+#[derive(Deserialize, Clone)]
+pub(crate) struct PowerUpDefinition {
+    pub(crate) kind: PowerUpKind,
+    pub(crate) x: i16,
+    pub(crate) y: i16,
+}
+
+#[derive(Deserialize, Clone)]
+pub(crate) struct CoinDefinition {
+    pub(crate) sprite: String,
+    pub(crate) x: i16,
+    pub(crate) y: i16,
+}
+
+pub(crate) struct Segment {
+    pub(crate) obstacles: Vec<Box<dyn Obstacle>>,
+    pub(crate) collectibles: Vec<Box<dyn Collectible>>,
 }
 
-pub(crate) fn platform_and_stone(
-    stone: HtmlImageElement,
+pub(crate) struct Segments {
+    definitions: Vec<SegmentDefinition>,
+    start: usize,
+    course: Vec<(usize, f32)>,
+    difficulty: DifficultyCurve,
+    shapes: CollisionShapes,
+    images: HashMap<String, ImageElement>,
     sprite_sheet: Rc<SpriteSheet>,
-    offset_x: i16,
-) -> Vec<Box<dyn Obstacle>> {
-    const INITIAL_STONE_OFFSET: i16 = 400;
-    const INITIAL_PLATFORM_OFFSET: i16 = 200;
-
-    vec![
-        Box::new(Barrier::new(Image::new(
-            stone,
+    power_up_sheet: Rc<SpriteSheet>,
+}
+
+impl Segments {
+    pub(crate) async fn load(
+        platform: &dyn engine::Platform,
+        json_path: &str,
+        sprite_sheet: Rc<SpriteSheet>,
+        power_up_sheet: Rc<SpriteSheet>,
+    ) -> Result<Self> {
+        let file = engine::load_json::<SegmentFile>(platform, json_path).await?;
+
+        let mut images = HashMap::new();
+        for image in file.segments.iter().flat_map(barrier_images) {
+            if !images.contains_key(image) {
+                images.insert(image.to_string(), platform.load_image(image).await?);
+            }
+        }
+
+        Self::new(file, images, sprite_sheet, power_up_sheet)
+    }
+
+    pub(crate) fn new(
+        file: SegmentFile,
+        images: HashMap<String, ImageElement>,
+        sprite_sheet: Rc<SpriteSheet>,
+        power_up_sheet: Rc<SpriteSheet>,
+    ) -> Result<Self> {
+        for segment in &file.segments {
+            validate(segment, &images, &sprite_sheet, &power_up_sheet)?;
+        }
+        file.difficulty.validate()?;
+        file.shapes.validate()?;
+
+        let index_of = |name: &String| {
+            file.segments
+                .iter()
+                .position(|segment| &segment.name == name)
+                .ok_or_else(|| anyhow!("Unknown segment {}", name))
+        };
+        let start = index_of(&file.start)?;
+        let course = file
+            .course
+            .iter()
+            .map(|entry| {
+                if !(0.0..=1.0).contains(&entry.difficulty) {
+                    return Err(anyhow!(
+                        "Difficulty of {} must be between 0 and 1",
+                        entry.name
+                    ));
+                }
+                Ok((index_of(&entry.name)?, entry.difficulty))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if course.is_empty() {
+            return Err(anyhow!("The course must list at least one segment"));
+        }
+
+        Ok(Segments {
+            definitions: file.segments,
+            start,
+            course,
+            difficulty: file.difficulty,
+            shapes: file.shapes,
+            images,
+            sprite_sheet,
+            power_up_sheet,
+        })
+    }
+
+    pub(crate) fn starting_segment(&self, offset_x: i16) -> Segment {
+        self.build(self.start, offset_x)
+    }
+
+    pub(crate) fn difficulty(&self) -> &DifficultyCurve {
+        &self.difficulty
+    }
+
+    pub(crate) fn course_weights(&self, distance: u32) -> Vec<f32> {
+        self.course
+            .iter()
+            .map(|(_, difficulty)| self.difficulty.segment_weight(*difficulty, distance))
+            .collect()
+    }
+
+    pub(crate) fn course_segment(&self, index: usize, offset_x: i16) -> Segment {
+        self.build(self.course[index].0, offset_x)
+    }
+
+    fn build(&self, segment: usize, offset_x: i16) -> Segment {
+        let definition = &self.definitions[segment];
+        Segment {
+            obstacles: definition
+                .obstacles
+                .iter()
+                .map(|obstacle| self.build_obstacle(obstacle, offset_x))
+                .collect(),
+            collectibles: definition
+                .power_ups
+                .iter()
+                .filter_map(|power_up| self.build_power_up(power_up, offset_x))
+                .chain(
+                    definition
+                        .coins
+                        .iter()
+                        .filter_map(|coin| self.build_coin(coin, offset_x)),
+                )
+                .collect(),
+        }
+    }
+
+    fn build_power_up(
+        &self,
+        power_up: &PowerUpDefinition,
+        offset_x: i16,
+    ) -> Option<Box<dyn Collectible>> {
+        let sprite = self
+            .power_up_sheet
+            .cell(&power_up.kind.sprite_name())?
+            .clone();
+        Some(Box::new(PowerUp::new(
+            self.power_up_sheet.clone(),
+            sprite,
+            power_up.kind,
             Point {
-                x: offset_x + INITIAL_STONE_OFFSET,
-                y: STONE_ON_GROUND,
+                x: offset_x + power_up.x,
+                y: power_up.y,
             },
-        ))),
-        Box::new(create_floating_platform(
-            sprite_sheet,
+        )))
+    }
+
+    fn build_coin(&self, coin: &CoinDefinition, offset_x: i16) -> Option<Box<dyn Collectible>> {
+        let sprite = self.sprite_sheet.cell(&coin.sprite)?.clone();
+        Some(Box::new(Coin::new(
+            self.sprite_sheet.clone(),
+            sprite,
             Point {
-                x: offset_x + INITIAL_PLATFORM_OFFSET,
-                y: HIGH_PLATFORM,
+                x: offset_x + coin.x,
+                y: coin.y,
             },
-        )),
-    ]
+        )))
+    }
+
+    fn build_obstacle(&self, obstacle: &ObstacleDefinition, offset_x: i16) -> Box<dyn Obstacle> {
+        match obstacle {
+            ObstacleDefinition::Barrier { image, x, y } => Box::new(Barrier::new(
+                Image::new(
+                    self.images[image].clone(),
+                    Point {
+                        x: offset_x + x,
+                        y: *y,
+                    },
+                ),
+                self.shapes_for(image),
+            )),
+            ObstacleDefinition::Platform {
+                sprites,
+                x,
+                y,
+                bounding_boxes,
+            } => {
+                let sprite_names: Vec<&str> = sprites.iter().map(String::as_str).collect();
+                let bounding_boxes: Vec<Rect> = bounding_boxes
+                    .iter()
+                    .map(|bounding_box| {
+                        Rect::new_from_x_y(
+                            bounding_box.x,
+                            bounding_box.y,
+                            bounding_box.w,
+                            bounding_box.h,
+                        )
+                    })
+                    .collect();
+
+                Box::new(Platform::new(
+                    self.sprite_sheet.clone(),
+                    Point {
+                        x: offset_x + x,
+                        y: *y,
+                    },
+                    &sprite_names,
+                    &bounding_boxes,
+                ))
+            }
+            ObstacleDefinition::Enemy {
+                sprite,
+                x,
+                y,
+                movement,
+            } => Box::new(Enemy::new(
+                self.sprite_sheet.clone(),
+                self.sprite_sheet
+                    .cell(sprite)
+                    .cloned()
+                    .expect("enemy sprites are validated on load"),
+                Point {
+                    x: offset_x + x,
+                    y: *y,
+                },
+                *movement,
+                self.shapes_for(sprite),
+            )),
+        }
+    }
+
+    fn shapes_for(&self, name: &str) -> Vec<Shape> {
+        self.shapes.shapes(name).unwrap_or_default().to_vec()
+    }
 }
 
-pub(crate) fn one_stone(stone: HtmlImageElement, offset_x: i16) -> Vec<Box<dyn Obstacle>> {
-    const INITIAL_STONE_OFFSET: i16 = 300;
+fn barrier_images(segment: &SegmentDefinition) -> impl Iterator<Item = &str> {
+    segment
+        .obstacles
+        .iter()
+        .filter_map(|obstacle| match obstacle {
+            ObstacleDefinition::Barrier { image, .. } => Some(image.as_str()),
+            ObstacleDefinition::Platform { .. } | ObstacleDefinition::Enemy { .. } => None,
+        })
+}
+
+fn validate(
+    segment: &SegmentDefinition,
+    images: &HashMap<String, ImageElement>,
+    sprite_sheet: &SpriteSheet,
+    power_up_sheet: &SpriteSheet,
+) -> Result<()> {
+    if segment.obstacles.is_empty() {
+        return Err(anyhow!("Segment {} has no obstacles", segment.name));
+    }
+    for obstacle in &segment.obstacles {
+        match obstacle {
+            ObstacleDefinition::Barrier { image, .. } => {
+                if !images.contains_key(image) {
+                    return Err(anyhow!(
+                        "Segment {} uses image {} which was not loaded",
+                        segment.name,
+                        image
+                    ));
+                }
+            }
+            ObstacleDefinition::Platform {
+                sprites,
+                bounding_boxes,
+                ..
+            } => {
+                if sprites.is_empty() {
+                    return Err(anyhow!(
+                        "Platform in segment {} has no sprites",
+                        segment.name
+                    ));
+                }
+                if let Some(sprite) = sprites
+                    .iter()
+                    .find(|sprite| sprite_sheet.cell(sprite).is_none())
+                {
+                    return Err(anyhow!(
+                        "Segment {} uses unknown sprite {}",
+                        segment.name,
+                        sprite
+                    ));
+                }
+                if bounding_boxes.is_empty() {
+                    return Err(anyhow!(
+                        "Platform in segment {} has no bounding boxes",
+                        segment.name
+                    ));
+                }
+                if bounding_boxes
+                    .iter()
+                    .any(|bounding_box| bounding_box.w <= 0 || bounding_box.h <= 0)
+                {
+                    return Err(anyhow!(
+                        "Platform in segment {} has an empty bounding box",
+                        segment.name
+                    ));
+                }
+            }
+            ObstacleDefinition::Enemy {
+                sprite, movement, ..
+            } => {
+                if sprite_sheet.cell(sprite).is_none() {
+                    return Err(anyhow!(
+                        "Segment {} uses unknown sprite {}",
+                        segment.name,
+                        sprite
+                    ));
+                }
+                match movement {
+                    Movement::Patrol { distance, .. } if *distance <= 0.0 => {
+                        return Err(anyhow!(
+                            "Patrolling enemy in segment {} has no distance to patrol",
+                            segment.name
+                        ));
+                    }
+                    Movement::Sine { period: 0, .. } => {
+                        return Err(anyhow!(
+                            "Flying enemy in segment {} has a period of 0",
+                            segment.name
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    if let Some(coin) = segment
+        .coins
+        .iter()
+        .find(|coin| sprite_sheet.cell(&coin.sprite).is_none())
+    {
+        return Err(anyhow!(
+            "Segment {} uses unknown sprite {}",
+            segment.name,
+            coin.sprite
+        ));
+    }
+    if let Some(power_up) = segment
+        .power_ups
+        .iter()
+        .find(|power_up| power_up_sheet.cell(&power_up.kind.sprite_name()).is_none())
+    {
+        return Err(anyhow!(
+            "Segment {} uses unknown power-up sprite {}",
+            segment.name,
+            power_up.kind.sprite_name()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::NativePlatform;
+    use futures::executor::block_on;
+
+    fn sheet(platform: &NativePlatform, name: &str) -> Rc<SpriteSheet> {
+        Rc::new(block_on(SpriteSheet::load(platform, name)).unwrap())
+    }
+
+    fn tiles(platform: &NativePlatform) -> Rc<SpriteSheet> {
+        sheet(platform, "tiles")
+    }
+
+    fn power_ups(platform: &NativePlatform) -> Rc<SpriteSheet> {
+        sheet(platform, "power_ups")
+    }
+
+    fn segment_file(json: &str) -> SegmentFile {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn loads_the_shipped_segments() {
+        let platform = NativePlatform::default();
+        let segments = block_on(Segments::load(
+            &platform,
+            "segments.json",
+            tiles(&platform),
+            power_ups(&platform),
+        ))
+        .unwrap();
+
+        assert_eq!(segments.course_weights(0).len(), 3);
+        let starting = segments.starting_segment(20);
+        assert_eq!(starting.obstacles.len(), 1);
+        assert_eq!(starting.obstacles[0].right(), 20 + 300 + 90);
+        assert!(starting.collectibles.is_empty());
+    }
+
+    #[test]
+    fn places_collectibles_relative_to_the_segment() {
+        let platform = NativePlatform::default();
+        let file = segment_file(
+            r#"{
+                "start": "boost",
+                "course": [{ "name": "boost", "difficulty": 0.5 }],
+                "segments": [{
+                    "name": "boost",
+                    "obstacles": [{
+                        "kind": "platform",
+                        "sprites": ["13.png"],
+                        "x": 0,
+                        "y": 0,
+                        "boundingBoxes": [{ "x": 0, "y": 0, "w": 10, "h": 10 }]
+                    }],
+                    "powerUps": [{ "kind": "speedBoost", "x": 100, "y": 400 }],
+                    "coins": [{ "sprite": "Coin.png", "x": 200, "y": 400 }]
+                }]
+            }"#,
+        );
+
+        let segments =
+            Segments::new(file, HashMap::new(), tiles(&platform), power_ups(&platform)).unwrap();
+        let segment = segments.course_segment(0, 50);
+        assert_eq!(segment.collectibles.len(), 2);
+        assert_eq!(segment.collectibles[0].right(), 50 + 100 + 40);
+        assert_eq!(segment.collectibles[1].right(), 50 + 200 + 32);
+    }
+
+    #[test]
+    fn rejects_coins_with_unknown_sprites() {
+        let platform = NativePlatform::default();
+        let file = segment_file(
+            r#"{
+                "start": "bad",
+                "course": [{ "name": "bad", "difficulty": 0.5 }],
+                "segments": [{
+                    "name": "bad",
+                    "obstacles": [{
+                        "kind": "platform",
+                        "sprites": ["13.png"],
+                        "x": 0,
+                        "y": 0,
+                        "boundingBoxes": [{ "x": 0, "y": 0, "w": 10, "h": 10 }]
+                    }],
+                    "coins": [{ "sprite": "Ruby.png", "x": 0, "y": 0 }]
+                }]
+            }"#,
+        );
+
+        let error = Segments::new(file, HashMap::new(), tiles(&platform), power_ups(&platform))
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Segment bad uses unknown sprite Ruby.png"
+        );
+    }
+
+    #[test]
+    fn rejects_power_ups_missing_from_their_sheet() {
+        let platform = NativePlatform::default();
+        let file = segment_file(
+            r#"{
+                "start": "bad",
+                "course": [{ "name": "bad", "difficulty": 0.5 }],
+                "segments": [{
+                    "name": "bad",
+                    "obstacles": [{
+                        "kind": "platform",
+                        "sprites": ["13.png"],
+                        "x": 0,
+                        "y": 0,
+                        "boundingBoxes": [{ "x": 0, "y": 0, "w": 10, "h": 10 }]
+                    }],
+                    "powerUps": [{ "kind": "magnet", "x": 0, "y": 0 }]
+                }]
+            }"#,
+        );
+
+        let error = Segments::new(file, HashMap::new(), tiles(&platform), tiles(&platform))
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Segment bad uses unknown power-up sprite Magnet.png"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_sprites() {
+        let platform = NativePlatform::default();
+        let file = segment_file(
+            r#"{
+                "start": "bad",
+                "course": [{ "name": "bad", "difficulty": 0.5 }],
+                "segments": [{
+                    "name": "bad",
+                    "obstacles": [{
+                        "kind": "platform",
+                        "sprites": ["not_a_tile.png"],
+                        "x": 0,
+                        "y": 0,
+                        "boundingBoxes": [{ "x": 0, "y": 0, "w": 10, "h": 10 }]
+                    }]
+                }]
+            }"#,
+        );
+
+        let error = Segments::new(file, HashMap::new(), tiles(&platform), power_ups(&platform))
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Segment bad uses unknown sprite not_a_tile.png"
+        );
+    }
+
+    #[test]
+    fn rejects_segments_without_obstacles() {
+        let platform = NativePlatform::default();
+        let file = segment_file(
+            r#"{
+                "start": "empty",
+                "course": [{ "name": "empty", "difficulty": 0.5 }],
+                "segments": [{ "name": "empty", "obstacles": [] }]
+            }"#,
+        );
+
+        let error = Segments::new(file, HashMap::new(), tiles(&platform), power_ups(&platform))
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "Segment empty has no obstacles");
+    }
+
+    #[test]
+    fn rejects_flying_enemies_without_a_period() {
+        let platform = NativePlatform::default();
+        let file = segment_file(
+            r#"{
+                "start": "bad",
+                "course": [{ "name": "bad", "difficulty": 0.5 }],
+                "segments": [{
+                    "name": "bad",
+                    "obstacles": [{
+                        "kind": "enemy",
+                        "sprite": "Flyer.png",
+                        "x": 0,
+                        "y": 300,
+                        "movement": "sine",
+                        "amplitude": 40,
+                        "period": 0
+                    }]
+                }]
+            }"#,
+        );
+
+        let error = Segments::new(file, HashMap::new(), tiles(&platform), power_ups(&platform))
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Flying enemy in segment bad has a period of 0"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_course_segments() {
+        let platform = NativePlatform::default();
+        let file = segment_file(
+            r#"{
+                "start": "gap",
+                "course": [{ "name": "missing", "difficulty": 0.5 }],
+                "segments": [{
+                    "name": "gap",
+                    "obstacles": [{
+                        "kind": "platform",
+                        "sprites": ["13.png"],
+                        "x": 0,
+                        "y": 0,
+                        "boundingBoxes": [{ "x": 0, "y": 0, "w": 10, "h": 10 }]
+                    }]
+                }]
+            }"#,
+        );
 
-    vec![Box::new(Barrier::new(Image::new(
-        stone,
-        Point {
-            x: offset_x + INITIAL_STONE_OFFSET,
-            y: STONE_ON_GROUND,
-        },
-    )))]
+        let error = Segments::new(file, HashMap::new(), tiles(&platform), power_ups(&platform))
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "Unknown segment missing");
+    }
 }
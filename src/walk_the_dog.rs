@@ -1,18 +1,35 @@
 mod barrier;
+mod bindings;
+mod coin;
+mod collectible;
+mod difficulty;
+mod dog;
+mod dog_state;
+mod enemy;
 pub mod game;
 mod obstacle;
 mod platform;
+mod power_up;
 pub mod redhatboy;
 pub mod redhatboy_state;
+mod score;
+#[cfg(test)]
+mod test_fixtures;
 mod walk_the_dog_state;
 mod walk_the_dog_state_machine;
 
 pub(super) use barrier::*;
+pub(super) use bindings::*;
+pub(super) use coin::*;
+pub(super) use collectible::*;
+pub(super) use difficulty::*;
+pub(super) use dog::Dog;
+pub(super) use enemy::*;
 pub(super) use game::WalkTheDog;
 pub(super) use obstacle::*;
 pub(super) use platform::*;
+pub(super) use power_up::*;
 pub(super) use redhatboy::RedHatBoy;
 pub(in crate::walk_the_dog) use redhatboy_state::*;
+pub(super) use score::*;
 pub(in crate::walk_the_dog) use walk_the_dog_state_machine::*;
-
-pub(super) use crate::browser;
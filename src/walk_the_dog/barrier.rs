@@ -1,25 +1,32 @@
-use crate::engine::Image;
+use crate::engine::{Image, Shape};
 
 use super::Obstacle;
 
 pub struct Barrier {
     image: Image,
+    shapes: Vec<Shape>,
 }
 
 impl Barrier {
-    pub fn new(image: Image) -> Self {
-        Barrier { image }
+    pub fn new(image: Image, shapes: Vec<Shape>) -> Self {
+        Barrier { image, shapes }
     }
 }
 
 impl Obstacle for Barrier {
-    fn check_intersection(&self, boy: &mut super::RedHatBoy) {
-        if boy.bounding_box().intersects(self.image.bounding_box()) {
+    fn check_intersection(&mut self, boy: &mut super::RedHatBoy) {
+        if boy.hit(self.image.bounding_box(), &self.shapes).is_some() {
             boy.knock_out();
         }
     }
 
-    fn draw(&self, renderer: &crate::engine::Renderer) {
+    fn support(&self, _boy: &super::RedHatBoy) -> Option<i16> {
+        None
+    }
+
+    fn update(&mut self) {}
+
+    fn draw(&self, renderer: &dyn crate::engine::Renderer) {
         self.image.draw(renderer);
         if cfg!(debug_assertions) {
             renderer.draw_rect(self.image.bounding_box());
@@ -30,7 +37,15 @@ impl Obstacle for Barrier {
         self.image.move_horizontally(x);
     }
 
+    fn left(&self) -> i16 {
+        self.image.bounding_box().x()
+    }
+
     fn right(&self) -> i16 {
         self.image.right()
     }
+
+    fn scores_when_passed(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,217 @@
+use crate::engine::{
+    self, axis_code, button_code, KeyState, GAMEPAD_PREFIX, TOUCH_PREFIX, TOUCH_SWIPE_DOWN,
+    TOUCH_TAP,
+};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const BINDINGS_KEY: &str = "walk_the_dog.bindings";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Action {
+    Run,
+    Jump,
+    Slide,
+    Pause,
+}
+
+impl Action {
+    pub(crate) const ALL: [Action; 4] = [Action::Run, Action::Jump, Action::Slide, Action::Pause];
+
+    pub(crate) fn button_id(&self) -> String {
+        format!("rebind_{:?}", self)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct Bindings {
+    codes: BTreeMap<Action, Vec<String>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        // Button numbers follow the standard gamepad mapping.
+        let codes = BTreeMap::from([
+            (
+                Action::Run,
+                vec![
+                    "ArrowRight".into(),
+                    button_code(15),
+                    axis_code("Right"),
+                    TOUCH_TAP.into(),
+                ],
+            ),
+            (
+                Action::Jump,
+                vec!["Space".into(), button_code(0), TOUCH_TAP.into()],
+            ),
+            (
+                Action::Slide,
+                vec![
+                    "ArrowDown".into(),
+                    button_code(13),
+                    axis_code("Down"),
+                    TOUCH_SWIPE_DOWN.into(),
+                ],
+            ),
+            (
+                Action::Pause,
+                vec!["Escape".into(), "KeyP".into(), button_code(9)],
+            ),
+        ]);
+        Bindings { codes }
+    }
+}
+
+impl Bindings {
+    /// Stored bindings override the defaults per action.
+    pub(crate) fn load(platform: &dyn engine::Platform) -> Self {
+        match platform.load_item(BINDINGS_KEY) {
+            Ok(Some(json)) => Bindings::from_json(&json),
+            Ok(None) => Bindings::default(),
+            Err(err) => {
+                error!("Could not load key bindings {:#?}", err);
+                Bindings::default()
+            }
+        }
+    }
+
+    pub(crate) fn from_json(json: &str) -> Self {
+        let mut bindings = Bindings::default();
+        match serde_json::from_str::<Bindings>(json) {
+            Ok(stored) => bindings.codes.extend(stored.codes),
+            Err(err) => error!("Discarding corrupt key bindings {:#?}", err),
+        }
+        bindings
+    }
+
+    pub(crate) fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self)
+            .map_err(|e| anyhow!("Could not serialize key bindings {:#?}", e))
+    }
+
+    pub(crate) fn save(&self, platform: &dyn engine::Platform) -> Result<()> {
+        platform.save_item(BINDINGS_KEY, &self.to_json()?)
+    }
+
+    pub(crate) fn is_active(&self, action: Action, keystate: &KeyState) -> bool {
+        self.codes(action)
+            .iter()
+            .any(|code| keystate.is_pressed(code))
+    }
+
+    pub(crate) fn just_activated(&self, action: Action, keystate: &KeyState) -> bool {
+        self.codes(action)
+            .iter()
+            .any(|code| keystate.just_pressed(code))
+    }
+
+    /// True when the last held key for the action goes up.
+    pub(crate) fn just_deactivated(&self, action: Action, keystate: &KeyState) -> bool {
+        let codes = self.codes(action);
+        codes.iter().any(|code| keystate.just_released(code))
+            && !codes.iter().any(|code| keystate.is_pressed(code))
+    }
+
+    pub(crate) fn codes(&self, action: Action) -> &[String] {
+        self.codes.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Gamepad and touch bindings are kept.
+    pub(crate) fn rebind(&mut self, action: Action, code: &str) {
+        self.codes
+            .values_mut()
+            .for_each(|codes| codes.retain(|bound| bound != code));
+
+        let codes = self.codes.entry(action).or_default();
+        codes.retain(|bound| bound.starts_with(GAMEPAD_PREFIX) || bound.starts_with(TOUCH_PREFIX));
+        codes.insert(0, code.into());
+    }
+
+    pub(crate) fn controls_ui(&self, rebinding: Option<Action>) -> String {
+        let buttons: String = Action::ALL
+            .iter()
+            .map(|&action| {
+                let key = if rebinding == Some(action) {
+                    "press a key"
+                } else {
+                    self.codes(action)
+                        .first()
+                        .map(String::as_str)
+                        .unwrap_or("none")
+                };
+                format!(
+                    "<button id='{}' class='binding'>{:?}: {}</button>",
+                    action.button_id(),
+                    action,
+                    key
+                )
+            })
+            .collect();
+        format!("<div id='controls'>{}</div>", buttons)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{NativePlatform, Platform};
+
+    fn keys(codes: &[&str]) -> KeyState {
+        let mut keystate = KeyState::new();
+        codes.iter().for_each(|code| keystate.set_pressed(code));
+        keystate
+    }
+
+    /// Touch gestures press and release their code within a single tick.
+    fn tap(code: &str) -> KeyState {
+        let mut keystate = KeyState::new();
+        keystate.set_pressed(code);
+        keystate.set_released(code);
+        keystate
+    }
+
+    #[test]
+    fn default_bindings_cover_keyboard_gamepad_and_touch() {
+        let bindings = Bindings::default();
+
+        assert!(bindings.is_active(Action::Jump, &keys(&["Space"])));
+        assert!(bindings.is_active(Action::Jump, &keys(&["GamepadButton0"])));
+        assert!(bindings.is_active(Action::Run, &keys(&["GamepadAxisRight"])));
+        assert!(!bindings.is_active(Action::Slide, &keys(&["Space"])));
+        assert!(bindings.just_activated(Action::Run, &tap(TOUCH_TAP)));
+        assert!(bindings.just_activated(Action::Jump, &tap(TOUCH_TAP)));
+        assert!(bindings.just_deactivated(Action::Jump, &tap(TOUCH_TAP)));
+        assert!(bindings.just_activated(Action::Slide, &tap(TOUCH_SWIPE_DOWN)));
+    }
+
+    #[test]
+    fn rebinding_moves_a_key_between_actions_and_persists() {
+        let platform = NativePlatform::default();
+        let mut bindings = Bindings::load(&platform);
+        bindings.rebind(Action::Jump, "ArrowDown");
+        bindings.save(&platform).unwrap();
+
+        let bindings = Bindings::load(&platform);
+        assert!(bindings.is_active(Action::Jump, &keys(&["ArrowDown"])));
+        assert!(!bindings.is_active(Action::Jump, &keys(&["Space"])));
+        assert!(bindings.is_active(Action::Jump, &keys(&["GamepadButton0"])));
+        assert!(!bindings.is_active(Action::Slide, &keys(&["ArrowDown"])));
+    }
+
+    #[test]
+    fn missing_actions_fall_back_to_defaults() {
+        let platform = NativePlatform::default();
+        platform
+            .save_item(BINDINGS_KEY, r#"{ "codes": { "Jump": ["KeyW"] } }"#)
+            .unwrap();
+
+        let bindings = Bindings::load(&platform);
+        assert_eq!(bindings.codes(Action::Jump), &["KeyW".to_string()]);
+        assert_eq!(
+            bindings.codes(Action::Run),
+            Bindings::default().codes(Action::Run)
+        );
+    }
+}
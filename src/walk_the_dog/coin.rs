@@ -0,0 +1,59 @@
+use super::{pull_towards, Collectible, RedHatBoy};
+use crate::engine::{Cell, Point, Rect, Renderer, SpriteSheet};
+use std::rc::Rc;
+
+pub(crate) struct Coin {
+    sheet: Rc<SpriteSheet>,
+    sprite: Cell,
+    position: Point,
+}
+
+impl Coin {
+    pub(crate) fn new(sheet: Rc<SpriteSheet>, sprite: Cell, position: Point) -> Self {
+        Coin {
+            sheet,
+            sprite,
+            position,
+        }
+    }
+}
+
+impl Collectible for Coin {
+    fn bounding_box(&self) -> Rect {
+        Rect::new_from_x_y(
+            self.position.x,
+            self.position.y,
+            self.sprite.frame.w,
+            self.sprite.frame.h,
+        )
+    }
+
+    fn collect(&self, _boy: &mut RedHatBoy) -> u32 {
+        1
+    }
+
+    fn draw(&self, renderer: &dyn Renderer) {
+        self.sheet.draw(
+            renderer,
+            &Rect::new_from_x_y(
+                self.sprite.frame.x,
+                self.sprite.frame.y,
+                self.sprite.frame.w,
+                self.sprite.frame.h,
+            ),
+            &self.bounding_box(),
+        );
+    }
+
+    fn move_horizontally(&mut self, x: i16) {
+        self.position.x += x;
+    }
+
+    fn attract_to(&mut self, target: Point) {
+        pull_towards(&mut self.position, target);
+    }
+
+    fn right(&self) -> i16 {
+        self.bounding_box().right()
+    }
+}
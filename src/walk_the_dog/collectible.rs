@@ -0,0 +1,27 @@
+use crate::engine::{Point, Rect, Renderer};
+
+use super::RedHatBoy;
+
+const MAGNET_RADIUS: i16 = 200;
+const MAGNET_PULL: i16 = 6;
+
+/// Never solid, unlike an `Obstacle`.
+pub trait Collectible {
+    fn bounding_box(&self) -> Rect;
+    /// Returns how many coins it counts for.
+    fn collect(&self, boy: &mut RedHatBoy) -> u32;
+    fn draw(&self, renderer: &dyn Renderer);
+    fn move_horizontally(&mut self, x: i16);
+    fn attract_to(&mut self, target: Point);
+    fn right(&self) -> i16;
+}
+
+pub(crate) fn pull_towards(position: &mut Point, target: Point) {
+    let dx = target.x - position.x;
+    let dy = target.y - position.y;
+    if dx.abs() > MAGNET_RADIUS || dy.abs() > MAGNET_RADIUS {
+        return;
+    }
+    position.x += dx.clamp(-MAGNET_PULL, MAGNET_PULL);
+    position.y += dy.clamp(-MAGNET_PULL, MAGNET_PULL);
+}
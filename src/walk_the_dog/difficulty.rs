@@ -0,0 +1,88 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+const MIN_SEGMENT_WEIGHT: f32 = 0.1;
+
+/// Speed and segment weights ramp up linearly over `ramp_distance`.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct DifficultyCurve {
+    pub(crate) ramp_distance: f32,
+    pub(crate) max_speed_multiplier: f32,
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        DifficultyCurve {
+            ramp_distance: 30000.0,
+            max_speed_multiplier: 2.0,
+        }
+    }
+}
+
+impl DifficultyCurve {
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self.ramp_distance <= 0.0 {
+            return Err(anyhow!("rampDistance must be positive"));
+        }
+        if self.max_speed_multiplier < 1.0 {
+            return Err(anyhow!("maxSpeedMultiplier must be at least 1"));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn progress(&self, distance: u32) -> f32 {
+        (distance as f32 / self.ramp_distance).min(1.0)
+    }
+
+    pub(crate) fn walking_speed(&self, base_speed: f32, distance: u32) -> f32 {
+        let multiplier = 1.0 + (self.max_speed_multiplier - 1.0) * self.progress(distance);
+        base_speed * multiplier
+    }
+
+    pub(crate) fn segment_weight(&self, segment_difficulty: f32, distance: u32) -> f32 {
+        let progress = self.progress(distance);
+        let weight = (1.0 - progress) * (1.0 - segment_difficulty) + progress * segment_difficulty;
+        weight.max(MIN_SEGMENT_WEIGHT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walking_speed_ramps_up_then_levels_off() {
+        let curve = DifficultyCurve {
+            ramp_distance: 1000.0,
+            max_speed_multiplier: 2.0,
+        };
+
+        assert_eq!(curve.walking_speed(4.0, 0), 4.0);
+        assert_eq!(curve.walking_speed(4.0, 125), 4.5);
+        assert_eq!(curve.walking_speed(4.0, 500), 6.0);
+        assert_eq!(curve.walking_speed(4.0, 1000), 8.0);
+        assert_eq!(curve.walking_speed(4.0, 5000), 8.0);
+        assert_eq!(curve.walking_speed(0.0, 5000), 0.0);
+    }
+
+    #[test]
+    fn weights_shift_towards_harder_segments() {
+        let curve = DifficultyCurve::default();
+        let far = curve.ramp_distance as u32;
+
+        assert!(curve.segment_weight(0.2, 0) > curve.segment_weight(0.8, 0));
+        assert!(curve.segment_weight(0.2, far) < curve.segment_weight(0.8, far));
+        assert_eq!(curve.segment_weight(1.0, 0), MIN_SEGMENT_WEIGHT);
+    }
+
+    #[test]
+    fn rejects_a_curve_that_slows_down() {
+        let curve = DifficultyCurve {
+            max_speed_multiplier: 0.5,
+            ..DifficultyCurve::default()
+        };
+
+        assert!(curve.validate().is_err());
+    }
+}
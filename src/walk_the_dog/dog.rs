@@ -0,0 +1,298 @@
+use super::dog_state::*;
+use super::RedHatBoy;
+use crate::engine::{Cell, ImageElement, Rect, Renderer, Sheet};
+
+const JUMP_REACTION_TICKS: u8 = 8;
+const LEASH_LENGTH: i16 = 30;
+const FOLLOW_EASING: f32 = 0.25;
+
+/// Never collides with obstacles.
+pub(crate) struct Dog {
+    state_machine: DogStateMachine,
+    sprite_sheet: Sheet,
+    image: ImageElement,
+    boy_was_jumping: bool,
+    jump_countdown: Option<u8>,
+}
+
+impl Dog {
+    pub(super) fn new(sprite_sheet: Sheet, image: ImageElement) -> Self {
+        Dog {
+            state_machine: DogStateMachine::Idle(DogState::new()),
+            sprite_sheet,
+            image,
+            boy_was_jumping: false,
+            jump_countdown: None,
+        }
+    }
+
+    pub(super) fn update(&mut self, boy: &RedHatBoy) {
+        let leash_end = f32::from(boy.pos_x() - LEASH_LENGTH);
+        let x = self.x();
+        self.state_machine.context_mut().position.x = match self.state_machine {
+            DogStateMachine::Idle(_) => leash_end,
+            DogStateMachine::Running(_) | DogStateMachine::Jumping(_) => {
+                x + (leash_end - x) * FOLLOW_EASING
+            }
+            DogStateMachine::Sitting(_) => x,
+        };
+
+        if boy.knocked_out() || (self.running() && boy.walking_speed() == 0.0) {
+            self.transition(Event::Sit);
+        } else if boy.walking_speed() > 0.0 {
+            self.transition(Event::Run);
+        }
+
+        let boy_jumping = boy.jumping();
+        if boy_jumping && !self.boy_was_jumping {
+            self.jump_countdown = Some(JUMP_REACTION_TICKS);
+        }
+        self.boy_was_jumping = boy_jumping;
+
+        self.jump_countdown = match self.jump_countdown {
+            Some(0) => {
+                self.transition(Event::Jump);
+                None
+            }
+            Some(ticks) => Some(ticks - 1),
+            None => None,
+        };
+
+        self.transition(Event::Update);
+    }
+
+    fn x(&self) -> f32 {
+        self.state_machine.context().position.x
+    }
+
+    fn running(&self) -> bool {
+        matches!(
+            self.state_machine,
+            DogStateMachine::Running(_) | DogStateMachine::Jumping(_)
+        )
+    }
+
+    fn transition(&mut self, event: Event) {
+        self.state_machine = self.state_machine.clone().transition(event);
+    }
+
+    fn frame_name(&self) -> String {
+        format!(
+            "{} ({}).png",
+            self.state_machine.frame_name(),
+            (self.state_machine.context().frame / 6) + 1
+        )
+    }
+
+    fn current_sprite(&self) -> Option<&Cell> {
+        self.sprite_sheet.frames.get(&self.frame_name())
+    }
+
+    pub(super) fn destination_box(&self) -> Option<Rect> {
+        let position = self.state_machine.context().position.round();
+        self.current_sprite().map(|sprite| {
+            Rect::new_from_x_y(
+                position.x + sprite.sprite_source_size.x,
+                position.y + sprite.sprite_source_size.y,
+                sprite.frame.w,
+                sprite.frame.h,
+            )
+        })
+    }
+
+    pub(super) fn draw(&self, renderer: &dyn Renderer) {
+        let (Some(sprite), Some(destination)) = (self.current_sprite(), self.destination_box())
+        else {
+            return;
+        };
+
+        renderer.draw_image(
+            self.image.as_ref(),
+            &Rect::new_from_x_y(
+                sprite.frame.x,
+                sprite.frame.y,
+                sprite.frame.w,
+                sprite.frame.h,
+            ),
+            &destination,
+        );
+    }
+
+    pub(super) fn move_horizontally(&mut self, x: i16) {
+        self.state_machine.context_mut().position.x += f32::from(x);
+    }
+
+    pub(super) fn reset(dog: Self) -> Self {
+        Self::new(dog.sprite_sheet, dog.image)
+    }
+}
+
+#[derive(Clone)]
+enum DogStateMachine {
+    Idle(DogState<Idle>),
+    Running(DogState<Running>),
+    Jumping(DogState<Jumping>),
+    Sitting(DogState<Sitting>),
+}
+
+enum Event {
+    Run,
+    Jump,
+    Sit,
+    Update,
+}
+
+impl DogStateMachine {
+    fn transition(self, event: Event) -> Self {
+        match (self.clone(), event) {
+            (DogStateMachine::Idle(state), Event::Run) => state.run().into(),
+            (DogStateMachine::Running(state), Event::Jump) => state.jump().into(),
+            (DogStateMachine::Running(state), Event::Sit) => state.sit().into(),
+            (DogStateMachine::Jumping(state), Event::Sit) => state.sit().into(),
+            (DogStateMachine::Idle(state), Event::Update) => state.update().into(),
+            (DogStateMachine::Running(state), Event::Update) => state.update().into(),
+            (DogStateMachine::Jumping(state), Event::Update) => state.update().into(),
+            (DogStateMachine::Sitting(state), Event::Update) => state.update().into(),
+            _ => self,
+        }
+    }
+
+    fn frame_name(&self) -> &str {
+        match self {
+            DogStateMachine::Idle(state) => state.frame_name(),
+            DogStateMachine::Running(state) => state.frame_name(),
+            DogStateMachine::Jumping(state) => state.frame_name(),
+            DogStateMachine::Sitting(state) => state.frame_name(),
+        }
+    }
+
+    fn context(&self) -> &DogContext {
+        match self {
+            DogStateMachine::Idle(state) => state.context(),
+            DogStateMachine::Running(state) => state.context(),
+            DogStateMachine::Jumping(state) => state.context(),
+            DogStateMachine::Sitting(state) => state.context(),
+        }
+    }
+
+    fn context_mut(&mut self) -> &mut DogContext {
+        match self {
+            DogStateMachine::Idle(state) => state.context_mut(),
+            DogStateMachine::Running(state) => state.context_mut(),
+            DogStateMachine::Jumping(state) => state.context_mut(),
+            DogStateMachine::Sitting(state) => state.context_mut(),
+        }
+    }
+}
+
+impl From<DogState<Idle>> for DogStateMachine {
+    fn from(state: DogState<Idle>) -> Self {
+        DogStateMachine::Idle(state)
+    }
+}
+
+impl From<DogState<Running>> for DogStateMachine {
+    fn from(state: DogState<Running>) -> Self {
+        DogStateMachine::Running(state)
+    }
+}
+
+impl From<DogState<Jumping>> for DogStateMachine {
+    fn from(state: DogState<Jumping>) -> Self {
+        DogStateMachine::Jumping(state)
+    }
+}
+
+impl From<DogState<Sitting>> for DogStateMachine {
+    fn from(state: DogState<Sitting>) -> Self {
+        DogStateMachine::Sitting(state)
+    }
+}
+
+impl From<JumpingEndState> for DogStateMachine {
+    fn from(end_state: JumpingEndState) -> Self {
+        match end_state {
+            JumpingEndState::Jumping(state) => state.into(),
+            JumpingEndState::Landing(state) => state.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::walk_the_dog::{game::Walk, test_fixtures::walk};
+
+    fn tick(walk: &mut Walk) {
+        walk.boy.update();
+        walk.dog.update(&walk.boy);
+    }
+
+    #[test]
+    fn the_dog_trails_behind_the_boy_and_lags_once_he_runs() {
+        let mut walk = walk();
+        tick(&mut walk);
+        assert_eq!(walk.dog.x(), f32::from(walk.boy.pos_x() - LEASH_LENGTH));
+
+        walk.boy.run_right();
+        for _ in 0..30 {
+            walk.boy.advance(4.0);
+            tick(&mut walk);
+        }
+
+        let lag = f32::from(walk.boy.pos_x() - LEASH_LENGTH) - walk.dog.x();
+        assert!(
+            lag > 0.0 && lag < f32::from(LEASH_LENGTH),
+            "lag was {}",
+            lag
+        );
+    }
+
+    #[test]
+    fn the_dog_copies_a_jump_after_a_moment() {
+        let mut walk = walk();
+        walk.boy.run_right();
+        tick(&mut walk);
+        assert!(walk.dog.running());
+
+        walk.boy.jump();
+        for _ in 0..JUMP_REACTION_TICKS {
+            tick(&mut walk);
+            assert!(!matches!(
+                walk.dog.state_machine,
+                DogStateMachine::Jumping(_)
+            ));
+        }
+        tick(&mut walk);
+        assert!(matches!(
+            walk.dog.state_machine,
+            DogStateMachine::Jumping(_)
+        ));
+    }
+
+    #[test]
+    fn the_dog_sits_when_the_boy_is_knocked_out() {
+        let mut walk = walk();
+        walk.boy.run_right();
+        tick(&mut walk);
+
+        walk.boy.knock_out();
+        tick(&mut walk);
+        assert!(matches!(
+            walk.dog.state_machine,
+            DogStateMachine::Sitting(_)
+        ));
+    }
+
+    #[test]
+    fn the_dog_waits_until_the_boy_starts_running() {
+        let mut walk = walk();
+        tick(&mut walk);
+
+        assert!(!walk.dog.running());
+        assert!(!matches!(
+            walk.dog.state_machine,
+            DogStateMachine::Sitting(_)
+        ));
+    }
+}
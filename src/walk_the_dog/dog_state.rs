@@ -0,0 +1,202 @@
+use super::game::HEIGHT;
+use crate::engine::Vector;
+
+const FLOOR: i16 = 544;
+const DOG_HEIGHT: i16 = HEIGHT - FLOOR;
+
+const IDLE_FRAMES: u8 = 11;
+const RUNNING_FRAMES: u8 = 23;
+const JUMPING_FRAMES: u8 = 11;
+const SITTING_FRAMES: u8 = 11;
+const IDLE_FRAME_NAME: &str = "Idle";
+const RUN_FRAME_NAME: &str = "Run";
+const JUMPING_FRAME_NAME: &str = "Jump";
+const SITTING_FRAME_NAME: &str = "Sit";
+
+const JUMP_SPEED: f32 = -18.0;
+const GRAVITY: f32 = 1.0;
+const TERMINAL_VELOCITY: f32 = 20.0;
+
+#[derive(Clone)]
+pub(super) struct DogState<S> {
+    context: DogContext,
+    _state: S,
+}
+
+impl<S> DogState<S> {
+    pub(super) fn context(&self) -> &DogContext {
+        &self.context
+    }
+
+    pub(super) fn context_mut(&mut self) -> &mut DogContext {
+        &mut self.context
+    }
+
+    fn update_context(&mut self, frames: u8) {
+        self.context = self.context.clone().update(frames);
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct Idle;
+
+impl DogState<Idle> {
+    pub(super) fn new() -> Self {
+        DogState {
+            context: DogContext {
+                frame: 0,
+                position: Vector {
+                    x: 0.0,
+                    y: f32::from(FLOOR),
+                },
+                velocity: 0.0,
+            },
+            _state: Idle {},
+        }
+    }
+
+    pub(super) fn frame_name(&self) -> &str {
+        IDLE_FRAME_NAME
+    }
+
+    pub(super) fn update(mut self) -> Self {
+        self.update_context(IDLE_FRAMES);
+        self
+    }
+
+    pub(super) fn run(self) -> DogState<Running> {
+        DogState {
+            context: self.context.reset_frame(),
+            _state: Running {},
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct Running;
+
+impl DogState<Running> {
+    pub(super) fn frame_name(&self) -> &str {
+        RUN_FRAME_NAME
+    }
+
+    pub(super) fn update(mut self) -> Self {
+        self.update_context(RUNNING_FRAMES);
+        self
+    }
+
+    pub(super) fn jump(self) -> DogState<Jumping> {
+        DogState {
+            context: self.context.reset_frame().set_vertical_velocity(JUMP_SPEED),
+            _state: Jumping {},
+        }
+    }
+
+    pub(super) fn sit(self) -> DogState<Sitting> {
+        DogState {
+            context: self.context.reset_frame(),
+            _state: Sitting {},
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct Jumping;
+
+pub(super) enum JumpingEndState {
+    Jumping(DogState<Jumping>),
+    Landing(DogState<Running>),
+}
+
+impl DogState<Jumping> {
+    pub(super) fn frame_name(&self) -> &str {
+        JUMPING_FRAME_NAME
+    }
+
+    pub(super) fn update(mut self) -> JumpingEndState {
+        self.update_context(JUMPING_FRAMES);
+
+        if self.context.position.y >= f32::from(FLOOR) {
+            JumpingEndState::Landing(self.land())
+        } else {
+            JumpingEndState::Jumping(self)
+        }
+    }
+
+    pub(super) fn sit(self) -> DogState<Sitting> {
+        DogState {
+            context: self.context.reset_frame().stop().set_on(HEIGHT),
+            _state: Sitting {},
+        }
+    }
+
+    fn land(self) -> DogState<Running> {
+        DogState {
+            context: self.context.reset_frame().set_on(HEIGHT),
+            _state: Running {},
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct Sitting;
+
+impl DogState<Sitting> {
+    pub(super) fn frame_name(&self) -> &str {
+        SITTING_FRAME_NAME
+    }
+
+    pub(super) fn update(mut self) -> Self {
+        self.update_context(SITTING_FRAMES);
+        self
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct DogContext {
+    pub(super) frame: u8,
+    pub(super) position: Vector,
+    pub(super) velocity: f32,
+}
+
+impl DogContext {
+    pub(super) fn update(mut self, frame_count: u8) -> Self {
+        if self.velocity < TERMINAL_VELOCITY {
+            self.velocity += GRAVITY;
+        }
+
+        if self.frame < frame_count {
+            self.frame += 1;
+        } else {
+            self.frame = 0;
+        }
+
+        self.position.y += self.velocity;
+
+        if self.position.y > f32::from(FLOOR) {
+            self.position.y = f32::from(FLOOR);
+        }
+
+        self
+    }
+
+    fn reset_frame(mut self) -> Self {
+        self.frame = 0;
+        self
+    }
+
+    fn set_vertical_velocity(mut self, y: f32) -> Self {
+        self.velocity = y;
+        self
+    }
+
+    fn stop(mut self) -> Self {
+        self.velocity = 0.0;
+        self
+    }
+
+    fn set_on(mut self, position: i16) -> Self {
+        self.position.y = f32::from(position - DOG_HEIGHT);
+        self
+    }
+}
@@ -0,0 +1,300 @@
+use super::{Obstacle, RedHatBoy};
+use crate::engine::{Cell, Point, Rect, Renderer, Shape, SpriteSheet, Vector};
+use serde::Deserialize;
+use std::{f32::consts::TAU, rc::Rc};
+
+/// How deep the boy's feet may be in a ground enemy and still land on it.
+const STOMP_TOLERANCE: i16 = 12;
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(tag = "movement", rename_all = "camelCase")]
+pub(crate) enum Movement {
+    /// Ground enemies can be defeated by landing on them.
+    Patrol {
+        distance: f32,
+        speed: f32,
+    },
+    Sine {
+        amplitude: f32,
+        period: u16,
+    },
+    Projectile {
+        speed: f32,
+    },
+}
+
+pub(crate) struct Enemy {
+    sheet: Rc<SpriteSheet>,
+    sprite: Cell,
+    position: Vector,
+    offset: Vector,
+    movement: Movement,
+    direction: f32,
+    ticks: u16,
+    defeated: bool,
+    shapes: Vec<Shape>,
+}
+
+impl Enemy {
+    pub(crate) fn new(
+        sheet: Rc<SpriteSheet>,
+        sprite: Cell,
+        position: Point,
+        movement: Movement,
+        shapes: Vec<Shape>,
+    ) -> Self {
+        Enemy {
+            sheet,
+            sprite,
+            position: Vector {
+                x: f32::from(position.x),
+                y: f32::from(position.y),
+            },
+            offset: Vector::default(),
+            movement,
+            direction: 1.0,
+            ticks: 0,
+            defeated: false,
+            shapes,
+        }
+    }
+
+    fn bounding_box(&self) -> Rect {
+        let position = (self.position + self.offset).round();
+        Rect::new_from_x_y(
+            position.x,
+            position.y,
+            self.sprite.frame.w,
+            self.sprite.frame.h,
+        )
+    }
+
+    fn stomped_by(&self, boy: &RedHatBoy) -> bool {
+        matches!(self.movement, Movement::Patrol { .. })
+            && boy.velocity_y() > 0
+            && boy.bounding_box().bottom() - boy.velocity_y()
+                <= self.bounding_box().y() + STOMP_TOLERANCE
+    }
+}
+
+impl Obstacle for Enemy {
+    fn check_intersection(&mut self, boy: &mut RedHatBoy) {
+        if self.defeated || !boy.collides_with(&self.bounding_box(), &self.shapes) {
+            return;
+        }
+
+        if self.stomped_by(boy) {
+            self.defeated = true;
+            boy.stomp();
+        } else {
+            boy.knock_out();
+        }
+    }
+
+    fn support(&self, _boy: &RedHatBoy) -> Option<i16> {
+        None
+    }
+
+    fn update(&mut self) {
+        match self.movement {
+            Movement::Patrol { distance, speed } => {
+                self.offset.x += speed * self.direction;
+                if self.offset.x <= 0.0 || self.offset.x >= distance {
+                    self.offset.x = self.offset.x.clamp(0.0, distance);
+                    self.direction = -self.direction;
+                }
+            }
+            Movement::Sine { amplitude, period } => {
+                self.ticks = (self.ticks + 1) % period;
+                let phase = TAU * self.ticks as f32 / period as f32;
+                self.offset.y = amplitude * phase.sin();
+            }
+            Movement::Projectile { speed } => {
+                self.offset.x -= speed;
+            }
+        }
+    }
+
+    fn draw(&self, renderer: &dyn Renderer) {
+        if self.defeated {
+            return;
+        }
+
+        self.sheet.draw(
+            renderer,
+            &Rect::new_from_x_y(
+                self.sprite.frame.x,
+                self.sprite.frame.y,
+                self.sprite.frame.w,
+                self.sprite.frame.h,
+            ),
+            &self.bounding_box(),
+        );
+        if cfg!(debug_assertions) {
+            renderer.draw_rect(&self.bounding_box());
+        }
+    }
+
+    fn move_horizontally(&mut self, x: i16) {
+        self.position.x += f32::from(x);
+    }
+
+    fn left(&self) -> i16 {
+        self.bounding_box().x()
+    }
+
+    fn right(&self) -> i16 {
+        self.bounding_box().right()
+    }
+
+    fn scores_when_passed(&self) -> bool {
+        !self.defeated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::walk_the_dog::test_fixtures::{boy, tiles, walk};
+
+    fn enemy(sprite: &str, position: Point, movement: Movement) -> Enemy {
+        let tiles = tiles();
+        let sprite = tiles.cell(sprite).unwrap().clone();
+        Enemy::new(tiles, sprite, position, movement, vec![])
+    }
+
+    #[test]
+    fn patrollers_turn_around_at_the_end_of_their_patrol() {
+        let mut enemy = enemy(
+            "Patroller.png",
+            Point { x: 100, y: 560 },
+            Movement::Patrol {
+                distance: 10.0,
+                speed: 4.0,
+            },
+        );
+
+        let positions: Vec<i16> = (0..6)
+            .map(|_| {
+                enemy.update();
+                enemy.bounding_box().x()
+            })
+            .collect();
+        assert_eq!(positions, vec![104, 108, 110, 106, 102, 100]);
+    }
+
+    #[test]
+    fn slow_projectiles_move_by_fractions_of_a_pixel() {
+        let mut enemy = enemy(
+            "Projectile.png",
+            Point { x: 100, y: 300 },
+            Movement::Projectile { speed: 0.25 },
+        );
+
+        let positions: Vec<i16> = (0..4)
+            .map(|_| {
+                enemy.update();
+                enemy.bounding_box().x()
+            })
+            .collect();
+        assert_eq!(positions, vec![100, 100, 99, 99]);
+    }
+
+    #[test]
+    fn flyers_stay_within_their_amplitude() {
+        let mut enemy = enemy(
+            "Flyer.png",
+            Point { x: 100, y: 300 },
+            Movement::Sine {
+                amplitude: 30.0,
+                period: 40,
+            },
+        );
+
+        let heights: Vec<i16> = (0..40)
+            .map(|_| {
+                enemy.update();
+                enemy.bounding_box().y()
+            })
+            .collect();
+        assert_eq!(heights.iter().min(), Some(&270));
+        assert_eq!(heights.iter().max(), Some(&330));
+        assert_eq!(heights.last(), Some(&300));
+    }
+
+    #[test]
+    fn landing_on_a_patroller_defeats_it() {
+        let mut boy = boy();
+        let mut enemy = enemy(
+            "Patroller.png",
+            Point { x: 0, y: 0 },
+            Movement::Patrol {
+                distance: 50.0,
+                speed: 1.0,
+            },
+        );
+        boy.run_right();
+        boy.update();
+        boy.jump();
+        while boy.velocity_y() <= 0 {
+            boy.update();
+        }
+        let feet = boy.bounding_box();
+        enemy.position = Vector {
+            x: f32::from(feet.x() + feet.width / 2 - enemy.sprite.frame.w / 2),
+            y: f32::from(feet.bottom() - 4),
+        };
+
+        enemy.check_intersection(&mut boy);
+
+        assert!(enemy.defeated);
+        assert!(boy.velocity_y() < 0);
+        assert!(!boy.knocked_out());
+    }
+
+    #[test]
+    fn running_into_a_flyer_knocks_the_boy_out() {
+        let mut boy = boy();
+        let mut enemy = enemy(
+            "Flyer.png",
+            Point { x: 0, y: 0 },
+            Movement::Sine {
+                amplitude: 30.0,
+                period: 40,
+            },
+        );
+        boy.run_right();
+        boy.update();
+        let boy_box = boy.bounding_box();
+        enemy.position = Vector {
+            x: f32::from(boy_box.x()),
+            y: f32::from(boy_box.y()),
+        };
+
+        enemy.check_intersection(&mut boy);
+        for _ in 0..30 {
+            boy.update();
+        }
+
+        assert!(!enemy.defeated);
+        assert!(boy.knocked_out());
+    }
+
+    #[test]
+    fn only_enemies_the_boy_got_past_score() {
+        let patrol = Movement::Patrol {
+            distance: 50.0,
+            speed: 1.0,
+        };
+        let passed = enemy("Patroller.png", Point { x: -500, y: 560 }, patrol);
+        let mut defeated = enemy("Patroller.png", Point { x: -500, y: 560 }, patrol);
+        let mut walk = walk();
+        defeated.defeated = true;
+        walk.obstacles = vec![Box::new(passed), Box::new(defeated)];
+
+        walk.clear_obstacles();
+
+        assert!(walk.obstacles.is_empty());
+        assert_eq!(walk.obstacles_cleared, 1);
+    }
+}
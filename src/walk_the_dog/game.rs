@@ -1,132 +1,345 @@
-use super::{browser, *};
+use super::*;
 use crate::{
-    engine::{self, Audio, Game, Image, KeyState, Point, Rect, Renderer, Sheet, SpriteSheet},
+    engine::{
+        self, Audio, Camera, Game, InputRecorder, KeyState, Parallax, Point, Recording, Rect,
+        Renderer, Sheet, Sound, SpriteSheet,
+    },
     segment::*,
 };
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use rand::prelude::*;
-use serde_wasm_bindgen::from_value;
+use futures::channel::mpsc::UnboundedReceiver;
+use rand::{distributions::WeightedIndex, prelude::*, rngs::StdRng};
 use std::rc::Rc;
-use web_sys::HtmlImageElement;
 
 pub(super) const HEIGHT: i16 = 600;
+const WIDTH: i16 = 600;
 const OBSTACLE_BUFFER: i16 = 20;
+/// Leaves room for the dog behind the boy.
+const BOY_SCREEN_X: i16 = 60;
+const CAMERA_EASING: f32 = 0.2;
+const SHAKE_TICKS: u8 = 20;
+const SHAKE_MAGNITUDE: i16 = 8;
+/// Positions are `i16`, so the world is shifted back before they overflow.
+const RECENTER_DISTANCE: i16 = 16000;
+const HUD_POSITION: Point = Point { x: 10, y: 30 };
+const HUD_LINE_HEIGHT: i16 = 25;
 
 pub(crate) struct WalkTheDog {
     machine: Option<WalkTheDogStateMachine>,
+    platform: Rc<dyn engine::Platform>,
+    seed: Option<u64>,
+    bindings: Option<Bindings>,
 }
 
 impl WalkTheDog {
-    pub(crate) fn new() -> Self {
-        WalkTheDog { machine: None }
+    pub(crate) fn new(platform: Rc<dyn engine::Platform>, seed: Option<u64>) -> Self {
+        WalkTheDog {
+            machine: None,
+            platform,
+            seed,
+            bindings: None,
+        }
+    }
+
+    pub(crate) fn replay(platform: Rc<dyn engine::Platform>, recording: &Recording) -> Self {
+        WalkTheDog {
+            machine: None,
+            platform,
+            seed: Some(recording.seed),
+            bindings: recording.bindings.as_deref().map(Bindings::from_json),
+        }
     }
 }
 
 pub(crate) struct Walk {
-    pub(super) obstacle_sheet: Rc<SpriteSheet>,
-    pub(super) stone: HtmlImageElement,
+    pub(super) platform: Rc<dyn engine::Platform>,
+    pub(super) segments: Segments,
     pub(super) boy: RedHatBoy,
-    pub(super) backgrounds: [Image; 2],
+    pub(super) dog: Dog,
+    pub(super) parallax: Parallax,
     pub(super) obstacles: Vec<Box<dyn Obstacle>>,
+    pub(super) collectibles: Vec<Box<dyn Collectible>>,
+    pub(super) coins: u32,
     pub(super) timeline: i16,
+    pub(super) distance: u32,
+    pub(super) camera: Camera,
+    pub(super) obstacles_cleared: u32,
+    pub(super) requested_seed: Option<u64>,
+    pub(super) seed: u64,
+    pub(super) rng: StdRng,
+    pub(super) recorder: InputRecorder,
+    pub(super) audio: Audio,
+    pub(super) collect_sound: Sound,
+    pub(super) visibility: UnboundedReceiver<bool>,
+    pub(super) bindings: Bindings,
 }
 
 impl Walk {
-    async fn new() -> Result<Self> {
-        let rhb_sheet = from_value::<Sheet>(browser::fetch_json("rhb.json").await?)
-            .map_err(|e| anyhow!("Failed to converting json to Sheet {}:#?", e))?;
-
-        let background = engine::load_image("BG.png").await?;
-        let stone = engine::load_image("Stone.png").await?;
-
-        let tiles = browser::fetch_json("tiles.json").await?;
-
-        let obstacle_sheet = Rc::new(SpriteSheet::new(
-            from_value::<Sheet>(tiles)
-                .map_err(|e| anyhow!("Failed to converting json to Sheet {}:#?", e))?,
-            engine::load_image("tiles.png").await?,
-        ));
+    pub(super) async fn new(
+        platform: Rc<dyn engine::Platform>,
+        requested_seed: Option<u64>,
+    ) -> Result<Self> {
+        let parallax = Parallax::load(platform.as_ref(), "scene.json").await?;
+
+        let obstacle_sheet = Rc::new(SpriteSheet::load(platform.as_ref(), "tiles").await?);
+        let power_up_sheet = Rc::new(SpriteSheet::load(platform.as_ref(), "power_ups").await?);
+        let segments = Segments::load(
+            platform.as_ref(),
+            "segments.json",
+            obstacle_sheet,
+            power_up_sheet,
+        )
+        .await?;
 
-        let audio = Audio::new()?;
-        let sound = audio.load_sound("SFX_Jump_23.mp3").await?;
+        let audio = platform.create_audio()?;
+        let collect_sound = audio.load_sound("SFX_Coin.wav").await?;
         let background_music = audio.load_sound("background_song.mp3").await?;
         audio.play_looping_sound(&background_music)?;
 
-        let boy = RedHatBoy::new(
-            rhb_sheet,
-            engine::load_image("rhb.png").await?,
-            audio,
-            sound,
+        let boy = RedHatBoy::load(platform.as_ref(), audio.clone()).await?;
+        let dog = Dog::new(
+            engine::load_json::<Sheet>(platform.as_ref(), "dog.json").await?,
+            platform.load_image("dog.png").await?,
         );
+        let visibility = platform.add_visibility_handler(audio.clone())?;
+        let bindings = Bindings::load(platform.as_ref());
 
-        let background_width = background.width() as i16;
-        let (obstacles, timeline) = Self::starting_obstacles_and_timeline(stone.clone(), 0);
+        let (starting_segment, timeline) = Self::starting_segment_and_timeline(&segments, 0);
+        let seed = choose_seed(requested_seed);
+        let camera = Self::camera_on(&boy);
 
         Ok(Self {
+            platform,
             boy,
-            backgrounds: [
-                Image::new(background.clone(), Point { x: 0, y: 0 }),
-                Image::new(
-                    background,
-                    Point {
-                        x: background_width,
-                        y: 0,
-                    },
-                ),
-            ],
-            obstacles,
-            obstacle_sheet,
-            stone,
+            dog,
+            parallax,
+            obstacles: starting_segment.obstacles,
+            collectibles: starting_segment.collectibles,
+            coins: 0,
+            segments,
             timeline,
+            distance: 0,
+            camera,
+            obstacles_cleared: 0,
+            requested_seed,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            recorder: InputRecorder::new(seed, bindings.to_json().ok()),
+            audio,
+            collect_sound,
+            visibility,
+            bindings,
         })
     }
 
-    fn starting_obstacles_and_timeline(
-        stone: HtmlImageElement,
-        offset_x: i16,
-    ) -> (Vec<Box<dyn Obstacle>>, i16) {
+    fn starting_segment_and_timeline(segments: &Segments, offset_x: i16) -> (Segment, i16) {
         const STARTING_TIMELINE_BUFFER: i16 = 200;
-        let obstacles = one_stone(stone, offset_x + OBSTACLE_BUFFER);
-        let timeline = rightmost(&obstacles) + STARTING_TIMELINE_BUFFER;
-        (obstacles, timeline)
+        let segment = segments.starting_segment(offset_x + OBSTACLE_BUFFER);
+        let timeline = rightmost(&segment.obstacles) + STARTING_TIMELINE_BUFFER;
+        (segment, timeline)
     }
 
-    pub(super) fn velocity(&self) -> i16 {
-        -self.boy.walking_speed()
+    pub(super) fn velocity(&self) -> f32 {
+        self.segments
+            .difficulty()
+            .walking_speed(self.boy.walking_speed(), self.distance)
     }
 
-    pub(super) fn generate_next_segment(&mut self) {
-        let mut rng = thread_rng();
-        let next_segment = rng.gen_range(0..2);
-
-        let mut next_obstacles = match next_segment {
-            0 => stone_and_platform(
-                self.stone.clone(),
-                self.obstacle_sheet.clone(),
-                self.timeline + OBSTACLE_BUFFER,
-            ),
-            1 => platform_and_stone(
-                self.stone.clone(),
-                self.obstacle_sheet.clone(),
-                self.timeline + OBSTACLE_BUFFER,
-            ),
-            _ => vec![],
-        };
+    pub(super) fn travel(&mut self, distance: i16) {
+        self.distance += distance.unsigned_abs() as u32;
+    }
 
-        self.timeline = rightmost(&next_obstacles);
-        self.obstacles.append(&mut next_obstacles);
+    pub(super) fn move_camera(&mut self) {
+        let before = self.camera.x();
+        self.camera.update();
+        self.camera
+            .follow(f32::from(self.boy.pos_x() - BOY_SCREEN_X), CAMERA_EASING);
+        self.parallax.scroll(self.camera.x() - before);
     }
 
-    pub(super) fn draw(&self, renderer: &Renderer) {
-        self.backgrounds
+    fn camera_on(boy: &RedHatBoy) -> Camera {
+        let mut camera = Camera::new(WIDTH, HEIGHT);
+        camera.follow(f32::from(boy.pos_x() - BOY_SCREEN_X), 1.0);
+        camera
+    }
+
+    pub(super) fn shake_camera(&mut self) {
+        self.camera.shake(SHAKE_TICKS, SHAKE_MAGNITUDE);
+    }
+
+    /// Only obstacles that score count towards `obstacles_cleared`.
+    pub(super) fn clear_obstacles(&mut self) {
+        let left = self.camera.visible().x();
+        let passed = self
+            .obstacles
             .iter()
-            .for_each(|background| background.draw(renderer));
-        self.boy.draw(renderer);
+            .filter(|obstacle| obstacle.right() <= left && obstacle.scores_when_passed())
+            .count();
+        self.obstacles.retain(|obstacle| obstacle.right() > left);
+        self.obstacles_cleared += passed as u32;
+    }
+
+    pub(super) fn check_footing(&mut self) {
+        if let Some(surface) = self
+            .obstacles
+            .iter()
+            .find_map(|obstacle| obstacle.support(&self.boy))
+        {
+            self.boy.land_on(surface);
+        }
+        self.boy.lose_footing();
+    }
 
-        self.obstacles.iter().for_each(|obstacle| {
-            obstacle.draw(renderer);
+    pub(super) fn collect_items(&mut self) {
+        let boy_box = self.boy.bounding_box();
+        let left = self.camera.visible().x();
+        let magnet = self.boy.power_ups().is_active(PowerUpKind::Magnet);
+        let mut collected = false;
+        self.collectibles.retain_mut(|collectible| {
+            if magnet {
+                collectible.attract_to(Point {
+                    x: boy_box.x() + boy_box.width / 2,
+                    y: boy_box.y() + boy_box.height / 2,
+                });
+            }
+            if boy_box.intersects(&collectible.bounding_box()) {
+                self.coins += collectible.collect(&mut self.boy);
+                collected = true;
+                false
+            } else {
+                collectible.right() > left
+            }
         });
+
+        if collected {
+            if let Err(err) = self.audio.play_sound(&self.collect_sound) {
+                log!("Error playing collect sound {:#?}", err);
+            }
+        }
+    }
+
+    pub(super) fn score(&self) -> u32 {
+        score(self.distance, self.obstacles_cleared)
+    }
+
+    pub(super) fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Paused ticks never reach the recording, so neither do pause keys.
+    pub(super) fn record(&mut self, keystate: &KeyState) {
+        let mut keystate = keystate.clone();
+        self.bindings
+            .codes(Action::Pause)
+            .iter()
+            .for_each(|code| keystate.forget(code));
+        self.recorder.record(&keystate);
+    }
+
+    pub(super) fn is_active(&self, action: Action, keystate: &KeyState) -> bool {
+        self.bindings.is_active(action, keystate)
+    }
+
+    /// Edge-triggered, so holding the key doesn't toggle.
+    pub(super) fn pause_pressed(&self, keystate: &KeyState) -> bool {
+        self.bindings.just_activated(Action::Pause, keystate)
+    }
+
+    /// True if the page was hidden at any point since the last call.
+    pub(super) fn hidden(&mut self) -> bool {
+        let mut hidden = false;
+        while let Ok(Some(now_hidden)) = self.visibility.try_next() {
+            hidden |= now_hidden;
+        }
+        hidden
+    }
+
+    pub(super) fn resume_after_hiding(&mut self) {
+        if self.hidden() {
+            if let Err(err) = self.audio.resume() {
+                error!("Could not resume audio {:#?}", err);
+            }
+        }
+    }
+
+    /// Not saved, as a replay uses the bindings it was recorded with.
+    pub(super) fn use_bindings(&mut self, bindings: Bindings) {
+        self.recorder = InputRecorder::new(self.seed, bindings.to_json().ok());
+        self.bindings = bindings;
+    }
+
+    pub(super) fn recording(&self) -> &Recording {
+        self.recorder.recording()
+    }
+
+    pub(super) fn generate_next_segment(&mut self) {
+        let next_segment = WeightedIndex::new(self.segments.course_weights(self.distance))
+            .map(|weights| weights.sample(&mut self.rng))
+            .unwrap_or(0);
+
+        let mut segment = self
+            .segments
+            .course_segment(next_segment, self.timeline + OBSTACLE_BUFFER);
+
+        self.timeline = rightmost(&segment.obstacles);
+        self.obstacles.append(&mut segment.obstacles);
+        self.collectibles.append(&mut segment.collectibles);
+    }
+
+    /// Background layers follow the camera's moves, not its position.
+    pub(super) fn recenter(&mut self) {
+        let shift = -self.camera.visible().x();
+        if -shift < RECENTER_DISTANCE {
+            return;
+        }
+
+        self.camera.move_horizontally(shift);
+        self.boy.move_horizontally(shift);
+        self.dog.move_horizontally(shift);
+        self.obstacles
+            .iter_mut()
+            .for_each(|obstacle| obstacle.move_horizontally(shift));
+        self.collectibles
+            .iter_mut()
+            .for_each(|collectible| collectible.move_horizontally(shift));
+        self.timeline += shift;
+    }
+
+    pub(super) fn draw(&self, renderer: &dyn Renderer) {
+        let visible = self.camera.visible();
+        renderer.set_camera(&self.camera);
+        self.parallax.draw(renderer, &self.camera);
+        self.dog.draw(renderer);
+        self.boy.draw(renderer);
+
+        self.obstacles
+            .iter()
+            .filter(|obstacle| obstacle.right() > visible.x() && obstacle.left() < visible.right())
+            .for_each(|obstacle| obstacle.draw(renderer));
+        self.collectibles
+            .iter()
+            .filter(|collectible| collectible.bounding_box().intersects(&visible))
+            .for_each(|collectible| collectible.draw(renderer));
+        renderer.reset_camera();
+
+        let hud = [
+            format!("Score {}", self.score()),
+            format!("Coins {}", self.coins),
+        ];
+        for (line, text) in hud
+            .into_iter()
+            .chain(self.boy.power_ups().hud())
+            .enumerate()
+        {
+            let position = Point {
+                x: HUD_POSITION.x,
+                y: HUD_POSITION.y + HUD_LINE_HEIGHT * line as i16,
+            };
+            if let Err(err) = renderer.draw_text(&text, &position) {
+                error!("Could not draw the HUD {:#?}", err);
+            }
+        }
     }
 
     pub(super) fn knocked_out(&self) -> bool {
@@ -134,14 +347,32 @@ impl Walk {
     }
 
     pub(super) fn reset(walk: Self) -> Self {
-        let (obstacles, timeline) = Self::starting_obstacles_and_timeline(walk.stone.clone(), 0);
+        let (starting_segment, timeline) = Self::starting_segment_and_timeline(&walk.segments, 0);
+        let seed = choose_seed(walk.requested_seed);
+        let mut parallax = walk.parallax;
+        parallax.reset();
+        let boy = RedHatBoy::reset(walk.boy);
         Walk {
-            boy: RedHatBoy::reset(walk.boy),
-            backgrounds: walk.backgrounds,
-            obstacles,
-            obstacle_sheet: walk.obstacle_sheet,
-            stone: walk.stone,
+            platform: walk.platform,
+            camera: Self::camera_on(&boy),
+            boy,
+            dog: Dog::reset(walk.dog),
+            parallax,
+            obstacles: starting_segment.obstacles,
+            collectibles: starting_segment.collectibles,
+            coins: 0,
+            segments: walk.segments,
             timeline,
+            distance: 0,
+            obstacles_cleared: 0,
+            requested_seed: walk.requested_seed,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            recorder: InputRecorder::new(seed, walk.bindings.to_json().ok()),
+            audio: walk.audio,
+            collect_sound: walk.collect_sound,
+            visibility: walk.visibility,
+            bindings: walk.bindings,
         }
     }
 }
@@ -151,9 +382,16 @@ impl Game for WalkTheDog {
     async fn initialize(&self) -> Result<Box<dyn Game>> {
         match self.machine {
             None => {
-                let machine = WalkTheDogStateMachine::new(Walk::new().await?);
+                let mut walk = Walk::new(self.platform.clone(), self.seed).await?;
+                if let Some(bindings) = &self.bindings {
+                    walk.use_bindings(bindings.clone());
+                }
+                let machine = WalkTheDogStateMachine::new(walk);
                 Ok(Box::new(WalkTheDog {
                     machine: Some(machine),
+                    platform: self.platform.clone(),
+                    seed: self.seed,
+                    bindings: self.bindings.clone(),
                 }))
             }
             Some(_) => Err(anyhow!("Error: Game is already initialized")),
@@ -167,8 +405,16 @@ impl Game for WalkTheDog {
         assert!(self.machine.is_some());
     }
 
-    fn draw(&mut self, renderer: &Renderer) {
-        renderer.clear(&Rect::new_from_x_y(0, 0, 600, HEIGHT));
+    fn resolution(&self) -> (i16, i16) {
+        (WIDTH, HEIGHT)
+    }
+
+    fn is_paused(&self) -> bool {
+        matches!(self.machine, Some(WalkTheDogStateMachine::Paused(_)))
+    }
+
+    fn draw(&mut self, renderer: &dyn Renderer, _alpha: f32) {
+        renderer.clear(&Rect::new_from_x_y(0, 0, WIDTH, HEIGHT));
 
         if let Some(machine) = &self.machine {
             machine.draw(renderer);
@@ -176,6 +422,10 @@ impl Game for WalkTheDog {
     }
 }
 
+fn choose_seed(requested_seed: Option<u64>) -> u64 {
+    requested_seed.unwrap_or_else(|| thread_rng().gen())
+}
+
 fn rightmost(obstacle_list: &[Box<dyn Obstacle>]) -> i16 {
     obstacle_list
         .iter()
@@ -187,11 +437,215 @@ fn rightmost(obstacle_list: &[Box<dyn Obstacle>]) -> i16 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use engine::Sound;
+    use engine::{NativePlatform, RecordingRenderer};
+    use futures::executor::block_on;
+    use test_fixtures::{tiles, walk};
+
+    #[test]
+    fn running_into_the_first_stone_ends_the_game_natively() {
+        let platform = Rc::new(NativePlatform::default());
+        let walk = block_on(Walk::new(platform.clone(), None)).unwrap();
+        let mut machine = WalkTheDogStateMachine::new(walk);
+        let mut keystate = KeyState::new();
+        keystate.set_pressed("ArrowRight");
+
+        let mut ticks = 0;
+        while !matches!(machine, WalkTheDogStateMachine::GameOver(_)) {
+            assert!(ticks < 1000, "the boy never hit the stone");
+            machine = machine.update(&keystate);
+            ticks += 1;
+        }
+
+        let WalkTheDogStateMachine::GameOver(game_over) = &machine else {
+            unreachable!()
+        };
+        let score = game_over.walk.score();
+        assert!(score > 0);
+        let ui = platform.ui();
+        assert_eq!(ui.len(), 1);
+        assert!(ui[0].contains(&score_ui(
+            score,
+            &HighScores::load(platform.as_ref()),
+            Some(0)
+        )));
+        assert!(ui[0].contains("<button id='new_game'>New Game</button>"));
+        assert!(ui[0].contains(&format!(
+            "download='replay.json' href='data:application/json,{{%22seed%22:{},",
+            game_over.walk.seed()
+        )));
+        assert_eq!(HighScores::load(platform.as_ref()).scores(), &[score]);
+        assert_eq!(
+            platform.audio().played(),
+            vec!["background_song.mp3".to_string()]
+        );
+
+        platform.click("new_game").unwrap();
+        machine = machine.update(&KeyState::new());
+
+        assert!(matches!(machine, WalkTheDogStateMachine::Ready(_)));
+        assert!(platform.ui().is_empty());
+    }
+
+    fn segment_sequence(seed: u64) -> Vec<(i16, i16, bool)> {
+        let mut walk = block_on(Walk::new(Rc::new(NativePlatform::default()), Some(seed))).unwrap();
+        (0..20).for_each(|_| walk.generate_next_segment());
+        walk.obstacles
+            .iter()
+            .map(|obstacle| {
+                (
+                    obstacle.left(),
+                    obstacle.right(),
+                    obstacle.scores_when_passed(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_generates_same_obstacles() {
+        assert_eq!(segment_sequence(42), segment_sequence(42));
+        assert_ne!(segment_sequence(42), segment_sequence(7));
+    }
+
+    #[test]
+    fn a_replay_uses_the_bindings_it_was_recorded_with() {
+        let recorded_on = NativePlatform::default();
+        let mut bindings = Bindings::load(&recorded_on);
+        bindings.rebind(Action::Jump, "KeyW");
+        bindings.save(&recorded_on).unwrap();
+        let walk = block_on(Walk::new(Rc::new(recorded_on), Some(1))).unwrap();
+
+        let replay = WalkTheDog::replay(Rc::new(NativePlatform::default()), walk.recording());
+
+        assert_eq!(replay.seed, Some(1));
+        assert_eq!(replay.bindings, Some(bindings));
+    }
+
+    #[test]
+    fn reset_keeps_a_requested_seed() {
+        let walk = block_on(Walk::new(Rc::new(NativePlatform::default()), Some(42))).unwrap();
+        let walk = Walk::reset(walk);
+
+        assert_eq!(walk.seed(), 42);
+    }
+
+    #[test]
+    fn walking_speeds_up_with_distance() {
+        let mut walk = walk();
+        walk.boy.run_right();
+        assert_eq!(walk.velocity(), 4.0);
+
+        walk.distance = walk.segments.difficulty().ramp_distance as u32;
+        assert_eq!(walk.velocity(), 8.0);
+    }
+
+    #[test]
+    fn draws_the_world_through_the_camera_then_the_hud() {
+        let mut walk = walk();
+        walk.boy.run_right();
+        walk.boy.update();
+        walk.dog.update(&walk.boy);
+        let renderer = RecordingRenderer::default();
+
+        walk.draw(&renderer);
+
+        renderer.assert_snapshot("walk_draw");
+    }
+
+    #[test]
+    fn advancing_carries_fractions_of_a_pixel_over() {
+        let mut walk = walk();
+
+        let pixels: Vec<i16> = (0..4).map(|_| walk.boy.advance(4.25)).collect();
+
+        assert_eq!(pixels, vec![4, 4, 5, 4]);
+        assert_eq!(walk.boy.pos_x(), -3);
+    }
+
+    #[test]
+    fn the_camera_follows_the_boy_and_leaves_obstacles_behind() {
+        let mut walk = walk();
+        let obstacles = walk.obstacles.len();
+        let first_right = walk.obstacles.iter().map(|o| o.right()).min().unwrap();
+
+        walk.boy
+            .advance(f32::from(first_right - walk.boy.pos_x() + BOY_SCREEN_X));
+        for _ in 0..60 {
+            walk.move_camera();
+        }
+        walk.clear_obstacles();
+
+        assert_eq!(walk.camera.visible().x(), first_right);
+        assert!(walk.obstacles.len() < obstacles);
+        assert_eq!(
+            walk.obstacles_cleared as usize,
+            obstacles - walk.obstacles.len()
+        );
+    }
+
+    #[test]
+    fn recentering_keeps_everything_in_place_on_screen() {
+        let mut walk = walk();
+        walk.boy.advance(f32::from(RECENTER_DISTANCE));
+        walk.dog.update(&walk.boy);
+        walk.camera.follow(f32::from(RECENTER_DISTANCE), 1.0);
+        let on_screen = |walk: &Walk| {
+            let left = walk.camera.visible().x();
+            (
+                walk.boy.pos_x() - left,
+                walk.dog.destination_box().unwrap().x() - left,
+                walk.obstacles[0].left() - left,
+                walk.timeline - left,
+            )
+        };
+        let before = on_screen(&walk);
+
+        walk.recenter();
+
+        assert_eq!(walk.camera.visible().x(), 0);
+        assert_eq!(on_screen(&walk), before);
+    }
+
+    fn coin_at(x: i16, y: i16) -> Box<dyn Collectible> {
+        let tiles = tiles();
+        let sprite = tiles.cell("Coin.png").unwrap().clone();
+        Box::new(Coin::new(tiles, sprite, Point { x, y }))
+    }
+
+    #[test]
+    fn collecting_a_coin_counts_it_and_plays_a_sound() {
+        let platform = Rc::new(NativePlatform::default());
+        let mut walk = block_on(Walk::new(platform.clone(), Some(1))).unwrap();
+        let boy_box = walk.boy.bounding_box();
+        walk.collectibles = vec![
+            coin_at(boy_box.x(), boy_box.y()),
+            coin_at(300, boy_box.y()),
+            coin_at(-200, 0),
+        ];
+
+        walk.collect_items();
+
+        assert_eq!(walk.coins, 1);
+        assert_eq!(walk.collectibles.len(), 1);
+        assert_eq!(
+            platform.audio().played(),
+            vec![
+                "background_song.mp3".to_string(),
+                "SFX_Coin.wav".to_string()
+            ]
+        );
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod web_tests {
+    use super::*;
+    use crate::browser;
+    use engine::{CollisionShapes, ImageElement, WebAudio, WebPlatform};
     use futures::channel::mpsc::unbounded;
     use std::collections::HashMap;
     use walk_the_dog_state::{GameOver, WalkTheDogState};
-    use web_sys::{AudioBuffer, AudioBufferOptions};
+    use web_sys::{AudioBuffer, AudioBufferOptions, HtmlImageElement};
 
     use wasm_bindgen_test::wasm_bindgen_test;
 
@@ -200,19 +654,21 @@ mod tests {
     #[wasm_bindgen_test]
     fn test_transation_from_game_over_to_new_game() {
         let (_, receiver) = unbounded();
-        let image = HtmlImageElement::new().unwrap();
-        let audio = Audio::new().unwrap();
+        let (_, visibility) = unbounded();
+        let image: ImageElement = Rc::new(HtmlImageElement::new().unwrap());
+        let audio = Audio::new(Rc::new(WebAudio::new().unwrap()));
         let options = AudioBufferOptions::new(1, 3000.0);
         let sound = Sound {
-            buffer: AudioBuffer::new(&options).unwrap(),
+            buffer: Rc::new(AudioBuffer::new(&options).unwrap()),
         };
         let rhb = RedHatBoy::new(
             Sheet {
                 frames: HashMap::new(),
             },
+            CollisionShapes::default(),
             image.clone(),
-            audio,
-            sound,
+            audio.clone(),
+            sound.clone(),
         );
 
         let sprite_sheet = SpriteSheet::new(
@@ -221,17 +677,68 @@ mod tests {
             },
             image.clone(),
         );
+        let segments = Segments::new(
+            SegmentFile {
+                start: "one_stone".into(),
+                course: vec![CourseEntry {
+                    name: "one_stone".into(),
+                    difficulty: 0.0,
+                }],
+                segments: vec![SegmentDefinition {
+                    name: "one_stone".into(),
+                    obstacles: vec![ObstacleDefinition::Barrier {
+                        image: "Stone.png".into(),
+                        x: 150,
+                        y: 546,
+                    }],
+                    power_ups: vec![],
+                    coins: vec![],
+                }],
+                difficulty: DifficultyCurve::default(),
+                shapes: CollisionShapes::default(),
+            },
+            HashMap::from([("Stone.png".to_string(), image.clone())]),
+            Rc::new(sprite_sheet),
+            Rc::new(SpriteSheet::new(
+                Sheet {
+                    frames: HashMap::new(),
+                },
+                image.clone(),
+            )),
+        )
+        .unwrap();
+        let (starting_segment, timeline) = Walk::starting_segment_and_timeline(&segments, 0);
 
         let walk = Walk {
+            platform: Rc::new(WebPlatform),
             boy: rhb,
-            backgrounds: [
-                Image::new(image.clone(), Point { x: 0, y: 0 }),
-                Image::new(image.clone(), Point { x: 0, y: 0 }),
-            ],
-            obstacles: vec![],
-            obstacle_sheet: Rc::new(sprite_sheet),
-            stone: image.clone(),
-            timeline: 0,
+            dog: Dog::new(
+                Sheet {
+                    frames: HashMap::new(),
+                },
+                image.clone(),
+            ),
+            parallax: Parallax::new(
+                serde_json::from_str(r#"{ "layers": [] }"#).unwrap(),
+                HashMap::new(),
+            )
+            .unwrap(),
+            obstacles: starting_segment.obstacles,
+            collectibles: starting_segment.collectibles,
+            coins: 0,
+            segments,
+            timeline,
+            distance: 0,
+            camera: Camera::new(WIDTH, HEIGHT),
+            obstacles_cleared: 0,
+            requested_seed: None,
+            seed: 0,
+            rng: StdRng::seed_from_u64(0),
+            recorder: InputRecorder::new(0, None),
+            audio,
+            collect_sound: sound,
+            visibility,
+            bindings: Bindings::default(),
         };
 
         let document = browser::document().unwrap();
@@ -244,8 +751,11 @@ mod tests {
         let state = WalkTheDogState {
             _state: GameOver {
                 new_game_event: receiver,
+                rebind_events: vec![],
+                rebinding: None,
+                summary: String::new(),
             },
-            walk: walk,
+            walk,
         };
 
         state.new_game();
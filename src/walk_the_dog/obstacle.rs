@@ -3,8 +3,13 @@ use crate::engine::Renderer;
 use super::RedHatBoy;
 
 pub trait Obstacle {
-    fn check_intersection(&self, boy: &mut RedHatBoy);
-    fn draw(&self, renderer: &Renderer);
+    fn check_intersection(&mut self, boy: &mut RedHatBoy);
+    /// The height the boy stands on, if this obstacle holds him up.
+    fn support(&self, boy: &RedHatBoy) -> Option<i16>;
+    fn update(&mut self);
+    fn draw(&self, renderer: &dyn Renderer);
     fn move_horizontally(&mut self, x: i16);
+    fn left(&self) -> i16;
     fn right(&self) -> i16;
+    fn scores_when_passed(&self) -> bool;
 }
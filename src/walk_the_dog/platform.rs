@@ -5,6 +5,9 @@ use crate::{
 };
 use std::rc::Rc;
 
+/// How far the boy's feet may sink into a platform and still stand on it.
+const SUPPORT_TOLERANCE: i16 = 2;
+
 pub(crate) struct Platform {
     sheet: Rc<SpriteSheet>,
     bounding_boxes: Vec<Rect>,
@@ -49,13 +52,14 @@ impl Platform {
 }
 
 impl Obstacle for Platform {
-    fn check_intersection(&self, boy: &mut RedHatBoy) {
-        if let Some(box_to_land_on) = self
-            .bounding_boxes()
-            .iter()
-            .find(|&bounding_box| boy.bounding_box().intersects(bounding_box))
+    fn check_intersection(&mut self, boy: &mut RedHatBoy) {
+        if let Some((box_to_land_on, contact)) =
+            self.bounding_boxes().iter().find_map(|bounding_box| {
+                boy.hit(bounding_box, &[])
+                    .map(|contact| (bounding_box, contact))
+            })
         {
-            if boy.velocity_y() > 0 && boy.pos_y() < self.position.y {
+            if contact.normal.y < 0 {
                 boy.land_on(box_to_land_on.y());
             } else {
                 boy.knock_out();
@@ -63,7 +67,25 @@ impl Obstacle for Platform {
         }
     }
 
-    fn draw(&self, renderer: &Renderer) {
+    fn support(&self, boy: &RedHatBoy) -> Option<i16> {
+        if boy.velocity_y() < 0 {
+            return None;
+        }
+        let body = boy.bounding_box();
+        self.bounding_boxes()
+            .iter()
+            .find(|&bounding_box| {
+                body.x() < bounding_box.right()
+                    && body.right() > bounding_box.x()
+                    && (bounding_box.y()..=bounding_box.y() + SUPPORT_TOLERANCE)
+                        .contains(&boy.feet())
+            })
+            .map(|bounding_box| bounding_box.y())
+    }
+
+    fn update(&mut self) {}
+
+    fn draw(&self, renderer: &dyn Renderer) {
         let mut x = 0;
         self.sprites.iter().for_each(|sprite| {
             self.sheet.draw(
@@ -98,10 +120,86 @@ impl Obstacle for Platform {
             .for_each(|bounding_box| bounding_box.set_x(bounding_box.position.x + x));
     }
 
+    fn left(&self) -> i16 {
+        self.position.x
+    }
+
     fn right(&self) -> i16 {
         self.bounding_boxes()
             .last()
             .unwrap_or(&Rect::default())
             .right()
     }
+
+    fn scores_when_passed(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::RecordingRenderer;
+    use crate::walk_the_dog::test_fixtures::{boy, tiles, walk};
+
+    #[test]
+    fn draws_its_sprites_side_by_side() {
+        let platform = Platform::new(
+            tiles(),
+            Point { x: 370, y: 420 },
+            &["13.png", "14.png", "15.png"],
+            &[
+                Rect::new_from_x_y(0, 0, 60, 54),
+                Rect::new_from_x_y(60, 0, 264, 93),
+                Rect::new_from_x_y(324, 0, 60, 54),
+            ],
+        );
+        let renderer = RecordingRenderer::default();
+
+        platform.draw(&renderer);
+
+        renderer.assert_snapshot("platform_draw");
+    }
+
+    #[test]
+    fn running_right_through_a_thin_platform_in_one_tick_still_hits_it() {
+        let mut boy = boy();
+        boy.run_right();
+        boy.update();
+        let body = boy.destination_box();
+        let mut thin = Platform::new(
+            tiles(),
+            Point {
+                x: body.right() + 2,
+                y: body.y(),
+            },
+            &[],
+            &[Rect::new_from_x_y(0, 0, 5, body.height)],
+        );
+
+        boy.advance(f32::from(body.width + 10));
+        assert!(thin.bounding_boxes()[0].right() < boy.destination_box().x());
+        thin.check_intersection(&mut boy);
+        for _ in 0..30 {
+            boy.update();
+        }
+
+        assert!(boy.knocked_out());
+    }
+
+    #[test]
+    fn platforms_left_behind_do_not_score() {
+        let mut walk = walk();
+        walk.obstacles = vec![Box::new(Platform::new(
+            tiles(),
+            Point { x: -500, y: 420 },
+            &["13.png"],
+            &[Rect::new_from_x_y(0, 0, 60, 54)],
+        ))];
+
+        walk.clear_obstacles();
+
+        assert!(walk.obstacles.is_empty());
+        assert_eq!(walk.obstacles_cleared, 0);
+    }
 }
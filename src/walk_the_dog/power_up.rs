@@ -0,0 +1,185 @@
+use super::{pull_towards, Collectible, RedHatBoy};
+use crate::engine::{Cell, Point, Rect, Renderer, SpriteSheet};
+use serde::Deserialize;
+use std::{collections::BTreeMap, rc::Rc};
+
+const TICKS_PER_SECOND: u16 = 60;
+const SHIELD_GRACE_TICKS: u8 = 60;
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum PowerUpKind {
+    DoubleJump,
+    Shield,
+    Magnet,
+    SpeedBoost,
+}
+
+impl PowerUpKind {
+    fn duration(&self) -> u16 {
+        match self {
+            PowerUpKind::DoubleJump => 10 * TICKS_PER_SECOND,
+            PowerUpKind::Shield => 15 * TICKS_PER_SECOND,
+            PowerUpKind::Magnet => 8 * TICKS_PER_SECOND,
+            PowerUpKind::SpeedBoost => 5 * TICKS_PER_SECOND,
+        }
+    }
+
+    fn label(&self) -> &str {
+        match self {
+            PowerUpKind::DoubleJump => "Double jump",
+            PowerUpKind::Shield => "Shield",
+            PowerUpKind::Magnet => "Magnet",
+            PowerUpKind::SpeedBoost => "Speed boost",
+        }
+    }
+
+    pub(crate) fn sprite_name(&self) -> String {
+        format!("{:?}.png", self)
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct PowerUps {
+    remaining: BTreeMap<PowerUpKind, u16>,
+    grace: u8,
+}
+
+impl PowerUps {
+    /// Picking up a power-up that is already active restarts its timer.
+    pub(crate) fn grant(&mut self, kind: PowerUpKind) {
+        self.remaining.insert(kind, kind.duration());
+    }
+
+    pub(crate) fn is_active(&self, kind: PowerUpKind) -> bool {
+        self.remaining.contains_key(&kind)
+    }
+
+    pub(crate) fn tick(&mut self) {
+        self.remaining.values_mut().for_each(|ticks| *ticks -= 1);
+        self.remaining.retain(|_, ticks| *ticks > 0);
+        self.grace = self.grace.saturating_sub(1);
+    }
+
+    /// Breaking the shield also grants a short grace period.
+    pub(crate) fn absorb_hit(&mut self) -> bool {
+        if self.grace > 0 {
+            true
+        } else if self.remaining.remove(&PowerUpKind::Shield).is_some() {
+            self.grace = SHIELD_GRACE_TICKS;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub(crate) fn hud(&self) -> Vec<String> {
+        self.remaining
+            .iter()
+            .map(|(kind, ticks)| format!("{} {}s", kind.label(), ticks.div_ceil(TICKS_PER_SECOND)))
+            .collect()
+    }
+}
+
+pub(crate) struct PowerUp {
+    sheet: Rc<SpriteSheet>,
+    sprite: Cell,
+    kind: PowerUpKind,
+    position: Point,
+}
+
+impl PowerUp {
+    pub(crate) fn new(
+        sheet: Rc<SpriteSheet>,
+        sprite: Cell,
+        kind: PowerUpKind,
+        position: Point,
+    ) -> Self {
+        PowerUp {
+            sheet,
+            sprite,
+            kind,
+            position,
+        }
+    }
+}
+
+impl Collectible for PowerUp {
+    fn bounding_box(&self) -> Rect {
+        Rect::new_from_x_y(
+            self.position.x,
+            self.position.y,
+            self.sprite.frame.w,
+            self.sprite.frame.h,
+        )
+    }
+
+    fn collect(&self, boy: &mut RedHatBoy) -> u32 {
+        boy.power_up(self.kind);
+        0
+    }
+
+    fn draw(&self, renderer: &dyn Renderer) {
+        self.sheet.draw(
+            renderer,
+            &Rect::new_from_x_y(
+                self.sprite.frame.x,
+                self.sprite.frame.y,
+                self.sprite.frame.w,
+                self.sprite.frame.h,
+            ),
+            &self.bounding_box(),
+        );
+    }
+
+    fn move_horizontally(&mut self, x: i16) {
+        self.position.x += x;
+    }
+
+    fn attract_to(&mut self, target: Point) {
+        pull_towards(&mut self.position, target);
+    }
+
+    fn right(&self) -> i16 {
+        self.bounding_box().right()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn power_ups_run_out() {
+        let mut power_ups = PowerUps::default();
+        power_ups.grant(PowerUpKind::SpeedBoost);
+        assert_eq!(power_ups.hud(), vec!["Speed boost 5s".to_string()]);
+
+        for _ in 1..PowerUpKind::SpeedBoost.duration() {
+            power_ups.tick();
+        }
+        assert!(power_ups.is_active(PowerUpKind::SpeedBoost));
+        assert_eq!(power_ups.hud(), vec!["Speed boost 1s".to_string()]);
+
+        power_ups.tick();
+        assert!(!power_ups.is_active(PowerUpKind::SpeedBoost));
+        assert!(power_ups.hud().is_empty());
+    }
+
+    #[test]
+    fn a_shield_absorbs_one_hit_and_then_a_grace_period() {
+        let mut power_ups = PowerUps::default();
+        assert!(!power_ups.absorb_hit());
+
+        power_ups.grant(PowerUpKind::Shield);
+        assert!(power_ups.absorb_hit());
+        assert!(!power_ups.is_active(PowerUpKind::Shield));
+
+        for _ in 1..SHIELD_GRACE_TICKS {
+            power_ups.tick();
+            assert!(power_ups.absorb_hit());
+        }
+        power_ups.tick();
+        assert!(!power_ups.absorb_hit());
+    }
+}
@@ -1,28 +1,46 @@
 use super::*;
-use crate::engine::{Audio, Cell, Rect, Renderer, Sheet, Sound};
-
-use web_sys::HtmlImageElement;
+use crate::engine::{
+    self, Audio, Cell, CollisionShapes, Contact, ImageElement, PlacedShapes, Point, Rect, Renderer,
+    Shape, Sheet, Sound,
+};
+use anyhow::Result;
 
 pub(crate) struct RedHatBoy {
     state_machine: RedHatBoyStateMachine,
     sprite_sheet: Sheet,
-    image: HtmlImageElement,
+    collision_shapes: CollisionShapes,
+    image: ImageElement,
+    advanced: i16,
 }
 
 impl RedHatBoy {
     pub(super) fn new(
         sprite_sheet: Sheet,
-        image: HtmlImageElement,
+        collision_shapes: CollisionShapes,
+        image: ImageElement,
         audio: Audio,
         jump_sound: Sound,
     ) -> Self {
         RedHatBoy {
             state_machine: RedHatBoyStateMachine::Idle(RedHatBoyState::new(audio, jump_sound)),
             sprite_sheet,
+            collision_shapes,
             image,
+            advanced: 0,
         }
     }
 
+    pub(super) async fn load(platform: &dyn engine::Platform, audio: Audio) -> Result<Self> {
+        let jump_sound = audio.load_sound("SFX_Jump_23.mp3").await?;
+        Ok(Self::new(
+            engine::load_json::<Sheet>(platform, "rhb.json").await?,
+            CollisionShapes::load(platform, "rhb_shapes.json").await?,
+            platform.load_image("rhb.png").await?,
+            audio,
+            jump_sound,
+        ))
+    }
+
     pub(super) fn run_right(&mut self) {
         self.state_machine = self.state_machine.clone().transition(Event::Run);
     }
@@ -35,28 +53,74 @@ impl RedHatBoy {
         self.state_machine = self.state_machine.clone().transition(Event::Jump);
     }
 
+    pub(super) fn release_jump(&mut self) {
+        self.state_machine = self.state_machine.clone().transition(Event::ReleaseJump);
+    }
+
     pub(super) fn knock_out(&mut self) {
         self.state_machine = self.state_machine.clone().transition(Event::KnockOut);
     }
 
+    pub(super) fn stomp(&mut self) {
+        self.state_machine = self.state_machine.clone().transition(Event::Stomp);
+    }
+
+    pub(super) fn power_up(&mut self, kind: PowerUpKind) {
+        self.state_machine = self.state_machine.clone().transition(Event::PowerUp(kind));
+    }
+
+    pub(super) fn power_ups(&self) -> &PowerUps {
+        &self.state_machine.context().power_ups
+    }
+
     pub(super) fn land_on(&mut self, position: i16) {
         self.state_machine = self.state_machine.clone().transition(Event::Land(position));
     }
 
+    /// Drops the boy unless something held him up since his last update.
+    pub(super) fn lose_footing(&mut self) {
+        self.state_machine = self.state_machine.clone().transition(Event::LoseFooting);
+    }
+
     pub(super) fn update(&mut self) {
         self.state_machine = self.state_machine.clone().update();
     }
 
+    fn position(&self) -> Point {
+        self.state_machine.context().position.round()
+    }
+
+    /// Returns the whole pixels he moved.
+    pub(super) fn advance(&mut self, distance: f32) -> i16 {
+        let before = self.pos_x();
+        self.state_machine.context_mut().position.x += distance;
+        self.advanced = self.pos_x() - before;
+        self.advanced
+    }
+
+    pub(super) fn move_horizontally(&mut self, x: i16) {
+        self.state_machine.context_mut().position.x += f32::from(x);
+    }
+
+    pub(super) fn pos_x(&self) -> i16 {
+        self.position().x
+    }
+
     pub(super) fn pos_y(&self) -> i16 {
-        self.state_machine.context().position.y
+        self.position().y
     }
 
+    pub(super) fn feet(&self) -> i16 {
+        self.pos_y() + PLAYER_HEIGHT
+    }
+
+    /// Negative while rising.
     pub(super) fn velocity_y(&self) -> i16 {
-        self.state_machine.context().velocity.y
+        self.state_machine.context().velocity.y.round() as i16
     }
 
-    pub(super) fn walking_speed(&self) -> i16 {
-        self.state_machine.context().velocity.x
+    pub(super) fn walking_speed(&self) -> f32 {
+        self.state_machine.context().walking_speed()
     }
 
     fn frame_name(&self) -> String {
@@ -83,22 +147,111 @@ impl RedHatBoy {
         )
     }
 
+    /// Either side without shapes falls back to its bounding box.
+    pub(super) fn collides_with(&self, bounds: &Rect, shapes: &[Shape]) -> bool {
+        self.collides_at(&Point::default(), bounds, shapes)
+    }
+
+    fn collides_at(&self, offset: &Point, bounds: &Rect, shapes: &[Shape]) -> bool {
+        let body = [Shape::from(&self.bounding_box())];
+        let (own_shapes, own_origin) = match self.shapes() {
+            Some(shapes) => (shapes, self.destination_box().position),
+            None => (&body[..], Point::default()),
+        };
+        let own = PlacedShapes {
+            shapes: own_shapes,
+            origin: Point {
+                x: own_origin.x + offset.x,
+                y: own_origin.y + offset.y,
+            },
+        };
+
+        let whole = [Shape::from(&Rect::new(
+            Point::default(),
+            bounds.width,
+            bounds.height,
+        ))];
+        let other = PlacedShapes {
+            shapes: if shapes.is_empty() { &whole } else { shapes },
+            origin: bounds.position,
+        };
+        own.overlaps(&other)
+    }
+
+    fn shapes(&self) -> Option<&[Shape]> {
+        self.collision_shapes
+            .shapes(&self.frame_name())
+            .filter(|shapes| !shapes.is_empty())
+    }
+
+    fn collision_box(&self) -> Rect {
+        let Some(shapes) = self.shapes() else {
+            return self.bounding_box();
+        };
+        let origin = self.destination_box().position;
+        shapes
+            .iter()
+            .map(|shape| shape.bounds(&origin))
+            .reduce(|a, b| {
+                let left = a.x().min(b.x());
+                let top = a.y().min(b.y());
+                Rect::new_from_x_y(
+                    left,
+                    top,
+                    a.right().max(b.right()) - left,
+                    a.bottom().max(b.bottom()) - top,
+                )
+            })
+            .unwrap_or_else(|| self.bounding_box())
+    }
+
+    /// Swept, so a fast boy can't pass through `rect`, but the contact only
+    /// counts if their shapes collide.
+    pub(super) fn hit(&self, rect: &Rect, shapes: &[Shape]) -> Option<Contact> {
+        let end = self.collision_box();
+        let motion = Point {
+            x: self.advanced,
+            y: self.velocity_y(),
+        };
+        let start = Rect::new_from_x_y(
+            end.x() - motion.x,
+            end.y() - motion.y,
+            end.width,
+            end.height,
+        );
+        let contact = start.sweep(&motion, rect)?;
+
+        let steps = motion.x.abs().max(motion.y.abs()).max(1);
+        let first = ((contact.time * f32::from(steps)).ceil() as i16).max(1);
+        (first..=steps)
+            .any(|step| {
+                let remaining = steps - step;
+                let offset = Point {
+                    x: -(motion.x * remaining / steps),
+                    y: -(motion.y * remaining / steps),
+                };
+                self.collides_at(&offset, rect, shapes)
+            })
+            .then_some(contact)
+    }
+
     pub(super) fn destination_box(&self) -> Rect {
         let sprite = self.current_sprite().expect("Cell not found");
+        let position = self.position();
 
         Rect::new_from_x_y(
-            self.state_machine.context().position.x + sprite.sprite_source_size.x,
-            self.state_machine.context().position.y + sprite.sprite_source_size.y,
+            position.x + sprite.sprite_source_size.x,
+            position.y + sprite.sprite_source_size.y,
             sprite.frame.w,
             sprite.frame.h,
         )
     }
 
-    pub(super) fn draw(&self, renderer: &Renderer) {
+    pub(super) fn draw(&self, renderer: &dyn Renderer) {
         let sprite = self.current_sprite().expect("Cell not found");
 
         renderer.draw_image(
-            &self.image,
+            self.image.as_ref(),
             &Rect::new_from_x_y(
                 sprite.frame.x,
                 sprite.frame.y,
@@ -117,9 +270,21 @@ impl RedHatBoy {
         self.state_machine.knocked_out()
     }
 
+    pub(super) fn falling(&self) -> bool {
+        matches!(
+            self.state_machine,
+            RedHatBoyStateMachine::Falling(_) | RedHatBoyStateMachine::KnockedOut(_)
+        )
+    }
+
+    pub(super) fn jumping(&self) -> bool {
+        matches!(self.state_machine, RedHatBoyStateMachine::Jumping(_))
+    }
+
     pub(super) fn reset(boy: Self) -> Self {
         Self::new(
             boy.sprite_sheet,
+            boy.collision_shapes,
             boy.image,
             boy.state_machine.context().audio.clone(),
             boy.state_machine.context().jump_sound.clone(),
@@ -133,6 +298,7 @@ enum RedHatBoyStateMachine {
     Running(RedHatBoyState<Running>),
     Sliding(RedHatBoyState<Sliding>),
     Jumping(RedHatBoyState<Jumping>),
+    Airborne(RedHatBoyState<Airborne>),
     Falling(RedHatBoyState<Falling>),
     KnockedOut(RedHatBoyState<KnockedOut>),
 }
@@ -140,9 +306,13 @@ enum RedHatBoyStateMachine {
 enum Event {
     Run,
     Jump,
+    ReleaseJump,
     Slide,
     KnockOut,
     Land(i16),
+    LoseFooting,
+    Stomp,
+    PowerUp(PowerUpKind),
     Update,
 }
 
@@ -150,7 +320,11 @@ impl RedHatBoyStateMachine {
     fn transition(self, event: Event) -> Self {
         match (self.clone(), event) {
             (RedHatBoyStateMachine::Idle(state), Event::Run) => state.run().into(),
-            (RedHatBoyStateMachine::Running(state), Event::Jump) => state.jump().into(),
+            (RedHatBoyStateMachine::Running(state), Event::Jump) => state.press_jump().into(),
+            (RedHatBoyStateMachine::Running(state), Event::ReleaseJump) => {
+                state.release_jump().into()
+            }
+            (RedHatBoyStateMachine::Jumping(state), Event::Jump) => state.press_jump().into(),
             (RedHatBoyStateMachine::Running(state), Event::Slide) => state.slide().into(),
             (RedHatBoyStateMachine::Running(state), Event::KnockOut) => state.knock_out().into(),
             (RedHatBoyStateMachine::Running(state), Event::Land(position)) => {
@@ -159,15 +333,47 @@ impl RedHatBoyStateMachine {
             (RedHatBoyStateMachine::Jumping(state), Event::Land(position)) => {
                 state.land_on(position).into()
             }
+            (RedHatBoyStateMachine::Jumping(state), Event::ReleaseJump) => {
+                state.release_jump().into()
+            }
             (RedHatBoyStateMachine::Jumping(state), Event::KnockOut) => state.knock_out().into(),
+            (RedHatBoyStateMachine::Airborne(state), Event::Jump) => state.press_jump().into(),
+            (RedHatBoyStateMachine::Airborne(state), Event::ReleaseJump) => {
+                state.release_jump().into()
+            }
+            (RedHatBoyStateMachine::Airborne(state), Event::Land(position)) => {
+                state.land_on(position).into()
+            }
+            (RedHatBoyStateMachine::Airborne(state), Event::KnockOut) => state.knock_out().into(),
+            (RedHatBoyStateMachine::Running(state), Event::LoseFooting) => {
+                state.lose_footing().into()
+            }
+            (RedHatBoyStateMachine::Sliding(state), Event::LoseFooting) => {
+                state.lose_footing().into()
+            }
             (RedHatBoyStateMachine::Sliding(state), Event::KnockOut) => state.knock_out().into(),
             (RedHatBoyStateMachine::Sliding(state), Event::Land(position)) => {
                 state.land_on(position).into()
             }
+            (RedHatBoyStateMachine::Jumping(state), Event::Stomp) => state.bounce().into(),
+            (RedHatBoyStateMachine::Airborne(state), Event::Stomp) => state.bounce().into(),
+            (RedHatBoyStateMachine::Running(state), Event::PowerUp(kind)) => {
+                state.power_up(kind).into()
+            }
+            (RedHatBoyStateMachine::Jumping(state), Event::PowerUp(kind)) => {
+                state.power_up(kind).into()
+            }
+            (RedHatBoyStateMachine::Sliding(state), Event::PowerUp(kind)) => {
+                state.power_up(kind).into()
+            }
+            (RedHatBoyStateMachine::Airborne(state), Event::PowerUp(kind)) => {
+                state.power_up(kind).into()
+            }
             (RedHatBoyStateMachine::Idle(state), Event::Update) => state.update().into(),
             (RedHatBoyStateMachine::Running(state), Event::Update) => state.update().into(),
             (RedHatBoyStateMachine::Jumping(state), Event::Update) => state.update().into(),
             (RedHatBoyStateMachine::Sliding(state), Event::Update) => state.update().into(),
+            (RedHatBoyStateMachine::Airborne(state), Event::Update) => state.update().into(),
             (RedHatBoyStateMachine::Falling(state), Event::Update) => state.update().into(),
             _ => self,
         }
@@ -179,6 +385,7 @@ impl RedHatBoyStateMachine {
             RedHatBoyStateMachine::Running(state) => state.frame_name(),
             RedHatBoyStateMachine::Jumping(state) => state.frame_name(),
             RedHatBoyStateMachine::Sliding(state) => state.frame_name(),
+            RedHatBoyStateMachine::Airborne(state) => state.frame_name(),
             RedHatBoyStateMachine::Falling(state) => state.frame_name(),
             RedHatBoyStateMachine::KnockedOut(state) => state.frame_name(),
         }
@@ -190,11 +397,24 @@ impl RedHatBoyStateMachine {
             RedHatBoyStateMachine::Running(state) => state.context(),
             RedHatBoyStateMachine::Jumping(state) => state.context(),
             RedHatBoyStateMachine::Sliding(state) => state.context(),
+            RedHatBoyStateMachine::Airborne(state) => state.context(),
             RedHatBoyStateMachine::Falling(state) => state.context(),
             RedHatBoyStateMachine::KnockedOut(state) => state.context(),
         }
     }
 
+    fn context_mut(&mut self) -> &mut RedHatBoyContext {
+        match self {
+            RedHatBoyStateMachine::Idle(state) => state.context_mut(),
+            RedHatBoyStateMachine::Running(state) => state.context_mut(),
+            RedHatBoyStateMachine::Jumping(state) => state.context_mut(),
+            RedHatBoyStateMachine::Sliding(state) => state.context_mut(),
+            RedHatBoyStateMachine::Airborne(state) => state.context_mut(),
+            RedHatBoyStateMachine::Falling(state) => state.context_mut(),
+            RedHatBoyStateMachine::KnockedOut(state) => state.context_mut(),
+        }
+    }
+
     fn update(self) -> Self {
         self.transition(Event::Update)
     }
@@ -228,6 +448,12 @@ impl From<RedHatBoyState<Jumping>> for RedHatBoyStateMachine {
     }
 }
 
+impl From<RedHatBoyState<Airborne>> for RedHatBoyStateMachine {
+    fn from(state: RedHatBoyState<Airborne>) -> Self {
+        RedHatBoyStateMachine::Airborne(state)
+    }
+}
+
 impl From<RedHatBoyState<Falling>> for RedHatBoyStateMachine {
     fn from(state: RedHatBoyState<Falling>) -> Self {
         RedHatBoyStateMachine::Falling(state)
@@ -245,6 +471,7 @@ impl From<SlidingEndState> for RedHatBoyStateMachine {
         match end_state {
             SlidingEndState::Sliding(state) => state.into(),
             SlidingEndState::Complete(state) => state.into(),
+            SlidingEndState::Airborne(state) => state.into(),
         }
     }
 }
@@ -258,6 +485,38 @@ impl From<JumpingEndState> for RedHatBoyStateMachine {
     }
 }
 
+impl From<RunningEndState> for RedHatBoyStateMachine {
+    fn from(end_state: RunningEndState) -> Self {
+        match end_state {
+            RunningEndState::Running(state) => state.into(),
+            RunningEndState::Jumping(state) => state.into(),
+            RunningEndState::Airborne(state) => state.into(),
+        }
+    }
+}
+
+impl From<AirborneEndState> for RedHatBoyStateMachine {
+    fn from(end_state: AirborneEndState) -> Self {
+        match end_state {
+            AirborneEndState::Airborne(state) => state.into(),
+            AirborneEndState::Jumping(state) => state.into(),
+            AirborneEndState::Landing(state) => state.into(),
+        }
+    }
+}
+
+impl<S> From<KnockOutEndState<S>> for RedHatBoyStateMachine
+where
+    RedHatBoyState<S>: Into<RedHatBoyStateMachine>,
+{
+    fn from(end_state: KnockOutEndState<S>) -> Self {
+        match end_state {
+            KnockOutEndState::Shielded(state) => state.into(),
+            KnockOutEndState::KnockedOut(state) => state.into(),
+        }
+    }
+}
+
 impl From<FallingEndState> for RedHatBoyStateMachine {
     fn from(end_state: FallingEndState) -> Self {
         match end_state {
@@ -266,3 +525,233 @@ impl From<FallingEndState> for RedHatBoyStateMachine {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{DrawCommand, NativePlatform, Platform, RecordingRenderer};
+    use crate::walk_the_dog::{game::HEIGHT, test_fixtures::boy};
+    use futures::executor::block_on;
+
+    fn running_boy() -> RedHatBoyStateMachine {
+        let platform = NativePlatform::default();
+        let audio = platform.create_audio().unwrap();
+        let jump_sound = block_on(audio.load_sound("SFX_Jump_23.mp3")).unwrap();
+        RedHatBoyStateMachine::Idle(RedHatBoyState::new(audio, jump_sound)).transition(Event::Run)
+    }
+
+    fn jumping(boy: &RedHatBoyStateMachine) -> bool {
+        matches!(boy, RedHatBoyStateMachine::Jumping(_))
+    }
+
+    fn peak_of_jump(ticks_held: usize) -> f32 {
+        let mut boy = running_boy().transition(Event::Jump);
+        let mut peak = boy.context().position.y;
+        for tick in 0.. {
+            if tick == ticks_held {
+                boy = boy.transition(Event::ReleaseJump);
+            }
+            boy = boy.update();
+            peak = peak.min(boy.context().position.y);
+            if !jumping(&boy) {
+                break;
+            }
+        }
+        peak
+    }
+
+    #[test]
+    fn holding_jump_longer_jumps_higher() {
+        assert!(peak_of_jump(1) > peak_of_jump(6));
+        assert!(peak_of_jump(6) > peak_of_jump(30));
+    }
+
+    #[test]
+    fn jumping_is_allowed_shortly_after_running_off_a_platform() {
+        let mut boy = running_boy().transition(Event::Land(300));
+        for _ in 0..COYOTE_TICKS {
+            boy = boy.update();
+        }
+        assert!(jumping(&boy.clone().transition(Event::Jump)));
+
+        for _ in 0..4 {
+            boy = boy.update();
+        }
+        assert!(!jumping(&boy.clone().transition(Event::Jump)));
+    }
+
+    #[test]
+    fn a_jump_pressed_just_before_landing_fires_on_landing() {
+        let mut boy = running_boy().transition(Event::Jump);
+        while boy.context().velocity.y < 0.0 || boy.context().position.y < 440.0 {
+            boy = boy.update();
+        }
+        boy = boy.transition(Event::Jump);
+        assert!(jumping(&boy));
+
+        let mut jumped_again = false;
+        for _ in 0..10 {
+            boy = boy.update();
+            jumped_again |= boy.context().velocity.y < 0.0;
+        }
+        assert!(jumped_again);
+        assert!(jumping(&boy));
+    }
+
+    #[test]
+    fn a_jump_pressed_too_early_is_forgotten() {
+        let mut boy = running_boy().transition(Event::Jump);
+        for _ in 0..3 {
+            boy = boy.update();
+        }
+        boy = boy.transition(Event::Jump).transition(Event::ReleaseJump);
+        while jumping(&boy) {
+            boy = boy.update();
+        }
+
+        assert!(matches!(boy, RedHatBoyStateMachine::Running(_)));
+    }
+
+    fn run_off_a_ledge() -> RedHatBoyStateMachine {
+        running_boy()
+            .transition(Event::Land(300))
+            .update()
+            .transition(Event::LoseFooting)
+    }
+
+    #[test]
+    fn running_off_a_ledge_falls_to_the_floor_and_keeps_running() {
+        let mut boy = run_off_a_ledge();
+        assert!(matches!(boy, RedHatBoyStateMachine::Airborne(_)));
+
+        while matches!(boy, RedHatBoyStateMachine::Airborne(_)) {
+            boy = boy.update();
+        }
+        assert!(matches!(boy, RedHatBoyStateMachine::Running(_)));
+        assert_eq!(boy.context().position.y, f32::from(HEIGHT - PLAYER_HEIGHT));
+        assert_eq!(boy.context().velocity.y, 0.0);
+    }
+
+    #[test]
+    fn standing_on_something_keeps_the_boy_running() {
+        let boy = running_boy()
+            .transition(Event::Land(300))
+            .update()
+            .transition(Event::Land(300))
+            .transition(Event::LoseFooting);
+
+        assert!(matches!(boy, RedHatBoyStateMachine::Running(_)));
+    }
+
+    #[test]
+    fn dropping_off_a_ledge_still_allows_a_coyote_jump() {
+        assert!(jumping(&run_off_a_ledge().transition(Event::Jump)));
+
+        let mut boy = run_off_a_ledge();
+        for _ in 0..COYOTE_TICKS {
+            boy = boy.update();
+        }
+        boy = boy.transition(Event::Jump);
+        assert!(matches!(boy, RedHatBoyStateMachine::Airborne(_)));
+    }
+
+    #[test]
+    fn a_double_jump_allows_one_more_jump_in_the_air() {
+        let mut boy = running_boy().transition(Event::Jump);
+        for _ in 0..10 {
+            boy = boy.update();
+        }
+        let velocity = boy.context().velocity.y;
+        assert_eq!(
+            boy.clone().transition(Event::Jump).context().velocity.y,
+            velocity
+        );
+
+        boy = boy
+            .transition(Event::PowerUp(PowerUpKind::DoubleJump))
+            .transition(Event::Jump);
+        assert!(boy.context().velocity.y < velocity);
+
+        let velocity = boy.context().velocity.y;
+        boy = boy.transition(Event::Jump);
+        assert_eq!(boy.context().velocity.y, velocity);
+    }
+
+    #[test]
+    fn a_shield_keeps_the_boy_running_through_one_hit() {
+        let boy = running_boy()
+            .transition(Event::PowerUp(PowerUpKind::Shield))
+            .transition(Event::KnockOut);
+        assert!(matches!(boy, RedHatBoyStateMachine::Running(_)));
+        assert!(!boy.context().power_ups.is_active(PowerUpKind::Shield));
+    }
+
+    #[test]
+    fn collision_shapes_are_tighter_than_the_bounding_box() {
+        let boy = boy();
+        let bounding_box = boy.bounding_box();
+        let corner = Rect::new_from_x_y(bounding_box.x(), bounding_box.y(), 1, 1);
+        let centre = Rect::new_from_x_y(
+            bounding_box.x() + bounding_box.width / 2,
+            bounding_box.y() + bounding_box.height / 2,
+            1,
+            1,
+        );
+
+        assert!(bounding_box.intersects(&corner));
+        assert!(!boy.collides_with(&corner, &[]));
+        assert!(boy.collides_with(&centre, &[]));
+    }
+
+    #[test]
+    fn obstacles_collide_by_their_shapes_not_their_bounds() {
+        let boy = boy();
+        let bounding_box = boy.bounding_box();
+        let corner = [Shape::Polygon {
+            points: vec![[0, 0], [2, 0], [0, 2]],
+        }];
+
+        assert!(boy.collides_with(&bounding_box, &[]));
+        assert!(!boy.collides_with(&bounding_box, &corner));
+    }
+
+    #[test]
+    fn an_obstacle_left_behind_the_boy_does_not_hit_him() {
+        let mut boy = boy();
+        boy.run_right();
+        boy.update();
+        boy.advance(4.0);
+        let sprite = boy.destination_box();
+        let behind = Rect::new_from_x_y(sprite.x() - 31, sprite.bottom() - 30, 30, 30);
+
+        assert!(
+            Rect::new_from_x_y(sprite.x() - 4, sprite.y(), sprite.width, sprite.height)
+                .intersects(&behind)
+        );
+        assert!(boy.hit(&behind, &[]).is_none());
+        assert!(boy
+            .hit(
+                &Rect::new_from_x_y(sprite.right() - 40, sprite.bottom() - 30, 30, 30),
+                &[]
+            )
+            .is_some());
+    }
+
+    #[test]
+    fn draws_the_current_frame_where_the_boy_is() {
+        let mut boy = boy();
+        boy.run_right();
+        for _ in 0..10 {
+            boy.update();
+        }
+        let renderer = RecordingRenderer::default();
+
+        boy.draw(&renderer);
+
+        let DrawCommand::Image { destination, .. } = &renderer.commands()[0] else {
+            panic!("the boy should be drawn first");
+        };
+        assert_eq!(*destination, boy.destination_box());
+        renderer.assert_snapshot("redhatboy_draw");
+    }
+}
@@ -1,10 +1,10 @@
-use super::game::HEIGHT;
-use crate::engine::{Audio, Point, Sound};
+use super::{game::HEIGHT, PowerUpKind, PowerUps};
+use crate::engine::{Audio, Sound, Vector};
 
 const FLOOR: i16 = 479;
-const PLAYER_HEIGHT: i16 = HEIGHT - FLOOR;
-const RUNNING_SPEED: i16 = 4;
-const STARTING_POINT: i16 = -20;
+pub(super) const PLAYER_HEIGHT: i16 = HEIGHT - FLOOR;
+const RUNNING_SPEED: f32 = 4.0;
+const STARTING_POINT: f32 = -20.0;
 
 const IDLE_FRAMES: u8 = 29;
 const RUNNING_FRAMES: u8 = 23;
@@ -16,11 +16,20 @@ const RUN_FRAME_NAME: &str = "Run";
 const SLIDING_FRAME_NAME: &str = "Slide";
 const JUMPING_FRAME_NAME: &str = "Jump";
 const FALLING_FRAME_NAME: &str = "Dead";
+const AIRBORNE_FRAME_NAME: &str = "Jump";
+const AIRBORNE_FIRST_FRAME: u8 = 27;
 
-const JUMP_SPEED: i16 = -25;
-const GRAVITY: i16 = 1;
+const JUMP_SPEED: f32 = -25.0;
+pub(super) const RELEASED_JUMP_SPEED: f32 = -8.0;
+/// Ticks after leaving the ground during which a jump is still allowed.
+pub(super) const COYOTE_TICKS: u8 = 6;
+/// Ticks a jump pressed in mid-air is remembered, to fire on landing.
+const JUMP_BUFFER_TICKS: u8 = 6;
+const SPEED_BOOST: f32 = 2.0;
+const BOUNCE_SPEED: f32 = -15.0;
+const GRAVITY: f32 = 1.0;
 
-const TERMINAL_VELOCITY: i16 = 20;
+const TERMINAL_VELOCITY: f32 = 20.0;
 
 #[derive(Clone)]
 pub(super) struct RedHatBoyState<S> {
@@ -33,9 +42,34 @@ impl<S> RedHatBoyState<S> {
         &self.context
     }
 
+    pub(super) fn context_mut(&mut self) -> &mut RedHatBoyContext {
+        &mut self.context
+    }
+
     fn update_context(&mut self, frames: u8) {
         self.context = self.context.clone().update(frames);
     }
+
+    pub(super) fn power_up(mut self, kind: PowerUpKind) -> Self {
+        self.context.power_ups.grant(kind);
+        self
+    }
+
+    pub(super) fn knock_out(mut self) -> KnockOutEndState<S> {
+        if self.context.power_ups.absorb_hit() {
+            KnockOutEndState::Shielded(self)
+        } else {
+            KnockOutEndState::KnockedOut(RedHatBoyState {
+                context: self.context.reset_frame().stop(),
+                _state: Falling {},
+            })
+        }
+    }
+}
+
+pub(super) enum KnockOutEndState<S> {
+    Shielded(RedHatBoyState<S>),
+    KnockedOut(RedHatBoyState<Falling>),
 }
 
 #[derive(Clone)]
@@ -46,11 +80,16 @@ impl RedHatBoyState<Idle> {
         RedHatBoyState {
             context: RedHatBoyContext {
                 frame: 0,
-                position: Point {
+                position: Vector {
                     x: STARTING_POINT,
-                    y: FLOOR,
+                    y: f32::from(FLOOR),
                 },
-                velocity: Point { x: 0, y: 0 },
+                velocity: Vector::default(),
+                ticks_since_grounded: 0,
+                jump_buffer: 0,
+                jump_held: false,
+                air_jumped: false,
+                power_ups: PowerUps::default(),
                 audio,
                 jump_sound,
             },
@@ -78,27 +117,52 @@ impl RedHatBoyState<Idle> {
 #[derive(Clone)]
 pub(super) struct Running;
 
+pub(super) enum RunningEndState {
+    Running(RedHatBoyState<Running>),
+    Jumping(RedHatBoyState<Jumping>),
+    Airborne(RedHatBoyState<Airborne>),
+}
+
 impl RedHatBoyState<Running> {
     pub(super) fn frame_name(&self) -> &str {
         RUN_FRAME_NAME
     }
 
-    pub(super) fn update(mut self) -> Self {
+    pub(super) fn update(mut self) -> RunningEndState {
         self.update_context(RUNNING_FRAMES);
+        self.jump_if_buffered()
+    }
+
+    pub(super) fn press_jump(mut self) -> RunningEndState {
+        self.context = self.context.hold_jump();
+        if self.context.ticks_since_grounded <= COYOTE_TICKS {
+            RunningEndState::Jumping(self.jump())
+        } else {
+            self.context = self.context.buffer_jump();
+            RunningEndState::Running(self)
+        }
+    }
+
+    pub(super) fn release_jump(mut self) -> Self {
+        self.context = self.context.release_jump();
         self
     }
 
-    pub(super) fn jump(self) -> RedHatBoyState<Jumping> {
+    fn jump(self) -> RedHatBoyState<Jumping> {
         RedHatBoyState {
-            context: self
-                .context
-                .reset_frame()
-                .set_vertical_velocity(JUMP_SPEED)
-                .play_jump_sound(),
+            context: self.context.reset_frame().start_jump().play_jump_sound(),
             _state: Jumping {},
         }
     }
 
+    fn jump_if_buffered(self) -> RunningEndState {
+        if self.context.jump_buffer > 0 && self.context.ticks_since_grounded == 0 {
+            RunningEndState::Jumping(self.jump())
+        } else {
+            RunningEndState::Running(self)
+        }
+    }
+
     pub(super) fn slide(self) -> RedHatBoyState<Sliding> {
         RedHatBoyState {
             context: self.context.reset_frame(),
@@ -106,18 +170,27 @@ impl RedHatBoyState<Running> {
         }
     }
 
-    pub(super) fn knock_out(self) -> RedHatBoyState<Falling> {
+    pub(super) fn lose_footing(self) -> RunningEndState {
+        if self.context.ticks_since_grounded > 0 {
+            RunningEndState::Airborne(self.drop_off())
+        } else {
+            RunningEndState::Running(self)
+        }
+    }
+
+    fn drop_off(self) -> RedHatBoyState<Airborne> {
         RedHatBoyState {
-            context: self.context.reset_frame().stop(),
-            _state: Falling {},
+            context: self.context.start_drop(),
+            _state: Airborne {},
         }
     }
 
-    pub(crate) fn land_on(self, position: i16) -> RedHatBoyState<Running> {
+    pub(crate) fn land_on(self, position: i16) -> RunningEndState {
         RedHatBoyState {
             context: self.context.set_on(position),
             _state: Running {},
         }
+        .jump_if_buffered()
     }
 }
 
@@ -126,7 +199,7 @@ pub(super) struct Jumping;
 
 pub(super) enum JumpingEndState {
     Jumping(RedHatBoyState<Jumping>),
-    Landing(RedHatBoyState<Running>),
+    Landing(RunningEndState),
 }
 
 impl RedHatBoyState<Jumping> {
@@ -134,28 +207,45 @@ impl RedHatBoyState<Jumping> {
         JUMPING_FRAME_NAME
     }
 
-    pub(super) fn knock_out(self) -> RedHatBoyState<Falling> {
-        RedHatBoyState {
-            context: self.context.reset_frame().stop(),
-            _state: Falling {},
-        }
+    /// With a double jump active the first mid-air press jumps again.
+    pub(super) fn press_jump(mut self) -> Self {
+        self.context = self.context.hold_jump();
+        self.context = if self.context.can_air_jump() {
+            self.context.reset_frame().air_jump()
+        } else {
+            self.context.buffer_jump()
+        };
+        self
+    }
+
+    pub(super) fn release_jump(mut self) -> Self {
+        self.context = self.context.release_jump();
+        self
     }
 
     pub(super) fn update(mut self) -> JumpingEndState {
         self.update_context(JUMPING_FRAMES);
 
-        if self.context.position.y >= FLOOR {
-            JumpingEndState::Landing(self.land_on(HEIGHT))
+        if self.context.position.y >= f32::from(FLOOR) {
+            self.land_on(HEIGHT)
         } else {
             JumpingEndState::Jumping(self)
         }
     }
 
-    pub(crate) fn land_on(self, position: i16) -> RedHatBoyState<Running> {
-        RedHatBoyState {
-            context: self.context.reset_frame().set_on(position),
-            _state: Running {},
-        }
+    pub(super) fn bounce(mut self) -> Self {
+        self.context = self.context.reset_frame().bounce();
+        self
+    }
+
+    pub(crate) fn land_on(self, position: i16) -> JumpingEndState {
+        JumpingEndState::Landing(
+            RedHatBoyState {
+                context: self.context.reset_frame().set_on(position),
+                _state: Running {},
+            }
+            .jump_if_buffered(),
+        )
     }
 }
 
@@ -165,6 +255,7 @@ pub(super) struct Sliding;
 pub(super) enum SlidingEndState {
     Sliding(RedHatBoyState<Sliding>),
     Complete(RedHatBoyState<Running>),
+    Airborne(RedHatBoyState<Airborne>),
 }
 
 impl RedHatBoyState<Sliding> {
@@ -179,13 +270,6 @@ impl RedHatBoyState<Sliding> {
         }
     }
 
-    pub(super) fn knock_out(self) -> RedHatBoyState<Falling> {
-        RedHatBoyState {
-            context: self.context.reset_frame().stop(),
-            _state: Falling {},
-        }
-    }
-
     pub(super) fn update(mut self) -> SlidingEndState {
         self.update_context(SLIDING_FRAMES);
 
@@ -202,6 +286,84 @@ impl RedHatBoyState<Sliding> {
             _state: Sliding {},
         }
     }
+
+    pub(super) fn lose_footing(self) -> SlidingEndState {
+        if self.context.ticks_since_grounded > 0 {
+            SlidingEndState::Airborne(RedHatBoyState {
+                context: self.context.start_drop(),
+                _state: Airborne {},
+            })
+        } else {
+            SlidingEndState::Sliding(self)
+        }
+    }
+}
+
+/// Dropped off an edge without jumping.
+#[derive(Clone)]
+pub(super) struct Airborne;
+
+pub(super) enum AirborneEndState {
+    Airborne(RedHatBoyState<Airborne>),
+    Jumping(RedHatBoyState<Jumping>),
+    Landing(RunningEndState),
+}
+
+impl RedHatBoyState<Airborne> {
+    pub(super) fn frame_name(&self) -> &str {
+        AIRBORNE_FRAME_NAME
+    }
+
+    pub(super) fn update(mut self) -> AirborneEndState {
+        self.update_context(JUMPING_FRAMES);
+        self.context.frame = self.context.frame.max(AIRBORNE_FIRST_FRAME);
+
+        if self.context.position.y >= f32::from(FLOOR) {
+            self.land_on(HEIGHT)
+        } else {
+            AirborneEndState::Airborne(self)
+        }
+    }
+
+    pub(super) fn press_jump(mut self) -> AirborneEndState {
+        self.context = self.context.hold_jump();
+        if self.context.ticks_since_grounded <= COYOTE_TICKS {
+            AirborneEndState::Jumping(RedHatBoyState {
+                context: self.context.reset_frame().start_jump().play_jump_sound(),
+                _state: Jumping {},
+            })
+        } else if self.context.can_air_jump() {
+            AirborneEndState::Jumping(RedHatBoyState {
+                context: self.context.reset_frame().air_jump(),
+                _state: Jumping {},
+            })
+        } else {
+            self.context = self.context.buffer_jump();
+            AirborneEndState::Airborne(self)
+        }
+    }
+
+    pub(super) fn release_jump(mut self) -> Self {
+        self.context = self.context.release_jump();
+        self
+    }
+
+    pub(super) fn bounce(self) -> RedHatBoyState<Jumping> {
+        RedHatBoyState {
+            context: self.context.reset_frame().bounce(),
+            _state: Jumping {},
+        }
+    }
+
+    pub(crate) fn land_on(self, position: i16) -> AirborneEndState {
+        AirborneEndState::Landing(
+            RedHatBoyState {
+                context: self.context.reset_frame().set_on(position),
+                _state: Running {},
+            }
+            .jump_if_buffered(),
+        )
+    }
 }
 
 #[derive(Clone)]
@@ -245,8 +407,13 @@ impl RedHatBoyState<KnockedOut> {
 #[derive(Clone)]
 pub(super) struct RedHatBoyContext {
     pub(super) frame: u8,
-    pub(super) position: Point,
-    pub(super) velocity: Point,
+    pub(super) position: Vector,
+    pub(super) velocity: Vector,
+    pub(super) ticks_since_grounded: u8,
+    pub(super) jump_buffer: u8,
+    pub(super) jump_held: bool,
+    pub(super) air_jumped: bool,
+    pub(super) power_ups: PowerUps,
     pub(super) audio: Audio,
     pub(super) jump_sound: Sound,
 }
@@ -265,9 +432,16 @@ impl RedHatBoyContext {
 
         self.position.y += self.velocity.y;
 
-        if self.position.y > FLOOR {
-            self.position.y = FLOOR;
+        if self.position.y >= f32::from(FLOOR) {
+            self.position.y = f32::from(FLOOR);
+            self.velocity.y = self.velocity.y.min(0.0);
+            self.ticks_since_grounded = 0;
+            self.air_jumped = false;
+        } else {
+            self.ticks_since_grounded = self.ticks_since_grounded.saturating_add(1);
         }
+        self.jump_buffer = self.jump_buffer.saturating_sub(1);
+        self.power_ups.tick();
 
         self
     }
@@ -277,8 +451,52 @@ impl RedHatBoyContext {
         self
     }
 
-    fn set_vertical_velocity(mut self, y: i16) -> Self {
-        self.velocity.y = y;
+    /// A buffered tap only gets the short hop.
+    fn start_jump(mut self) -> Self {
+        self.velocity.y = if self.jump_held {
+            JUMP_SPEED
+        } else {
+            RELEASED_JUMP_SPEED
+        };
+        self.jump_buffer = 0;
+        self
+    }
+
+    pub(super) fn walking_speed(&self) -> f32 {
+        if self.velocity.x > 0.0 && self.power_ups.is_active(PowerUpKind::SpeedBoost) {
+            self.velocity.x + SPEED_BOOST
+        } else {
+            self.velocity.x
+        }
+    }
+
+    fn can_air_jump(&self) -> bool {
+        !self.air_jumped && self.power_ups.is_active(PowerUpKind::DoubleJump)
+    }
+
+    fn air_jump(mut self) -> Self {
+        self.air_jumped = true;
+        self.start_jump().play_jump_sound()
+    }
+
+    fn bounce(mut self) -> Self {
+        self.velocity.y = BOUNCE_SPEED;
+        self
+    }
+
+    fn hold_jump(mut self) -> Self {
+        self.jump_held = true;
+        self
+    }
+
+    fn buffer_jump(mut self) -> Self {
+        self.jump_buffer = JUMP_BUFFER_TICKS;
+        self
+    }
+
+    fn release_jump(mut self) -> Self {
+        self.jump_held = false;
+        self.velocity.y = self.velocity.y.max(RELEASED_JUMP_SPEED);
         self
     }
 
@@ -288,14 +506,21 @@ impl RedHatBoyContext {
     }
 
     fn stop(mut self) -> Self {
-        self.velocity.x = 0;
-        self.velocity.y = 0;
+        self.velocity = Vector::default();
         self
     }
 
+    /// Landing also stops the fall, so walking off starts from rest.
     fn set_on(mut self, position: i16) -> Self {
-        let position = position - PLAYER_HEIGHT;
-        self.position.y = position;
+        self.position.y = f32::from(position - PLAYER_HEIGHT);
+        self.velocity.y = self.velocity.y.min(0.0);
+        self.ticks_since_grounded = 0;
+        self.air_jumped = false;
+        self
+    }
+
+    fn start_drop(mut self) -> Self {
+        self.frame = AIRBORNE_FIRST_FRAME;
         self
     }
 
@@ -0,0 +1,112 @@
+use crate::engine;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+const DISTANCE_PER_POINT: u32 = 10;
+const POINTS_PER_OBSTACLE: u32 = 50;
+const HIGH_SCORES_KEY: &str = "walk_the_dog.high_scores";
+const MAX_HIGH_SCORES: usize = 5;
+
+pub(crate) fn score(distance: u32, obstacles_cleared: u32) -> u32 {
+    distance / DISTANCE_PER_POINT + obstacles_cleared * POINTS_PER_OBSTACLE
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+pub(crate) struct HighScores {
+    scores: Vec<u32>,
+}
+
+impl HighScores {
+    pub(crate) fn load(platform: &dyn engine::Platform) -> Self {
+        match platform.load_item(HIGH_SCORES_KEY) {
+            Ok(Some(json)) => serde_json::from_str(&json).unwrap_or_else(|err| {
+                error!("Discarding corrupt high scores {:#?}", err);
+                HighScores::default()
+            }),
+            Ok(None) => HighScores::default(),
+            Err(err) => {
+                error!("Could not load high scores {:#?}", err);
+                HighScores::default()
+            }
+        }
+    }
+
+    pub(crate) fn save(&self, platform: &dyn engine::Platform) -> Result<()> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| anyhow!("Could not serialize high scores {:#?}", e))?;
+        platform.save_item(HIGH_SCORES_KEY, &json)
+    }
+
+    /// The zero-based rank of the new score, if it made the table.
+    pub(crate) fn insert(&mut self, score: u32) -> Option<usize> {
+        let rank = self.scores.iter().position(|&other| score > other);
+        let rank = rank.unwrap_or(self.scores.len());
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+
+        self.scores.insert(rank, score);
+        self.scores.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+
+    pub(crate) fn scores(&self) -> &[u32] {
+        &self.scores
+    }
+}
+
+pub(crate) fn score_ui(score: u32, high_scores: &HighScores, rank: Option<usize>) -> String {
+    let rows: String = high_scores
+        .scores()
+        .iter()
+        .enumerate()
+        .map(|(index, score)| {
+            let class = if Some(index) == rank {
+                " class='latest'"
+            } else {
+                ""
+            };
+            format!("<li{}>{}</li>", class, score)
+        })
+        .collect();
+
+    format!("<p>Score {}</p><ol>{}</ol>", score, rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{NativePlatform, Platform};
+
+    #[test]
+    fn keeps_the_best_scores_in_order() {
+        let mut high_scores = HighScores::default();
+        for score in [30, 10, 50, 20, 40] {
+            high_scores.insert(score);
+        }
+
+        assert_eq!(high_scores.insert(25), Some(3));
+        assert_eq!(high_scores.scores(), &[50, 40, 30, 25, 20]);
+        assert_eq!(high_scores.insert(5), None);
+        assert_eq!(high_scores.insert(60), Some(0));
+        assert_eq!(high_scores.scores(), &[60, 50, 40, 30, 25]);
+    }
+
+    #[test]
+    fn high_scores_survive_a_reload() {
+        let platform = NativePlatform::default();
+        let mut high_scores = HighScores::load(&platform);
+        high_scores.insert(120);
+        high_scores.save(&platform).unwrap();
+
+        assert_eq!(HighScores::load(&platform).scores(), &[120]);
+    }
+
+    #[test]
+    fn corrupt_high_scores_start_over() {
+        let platform = NativePlatform::default();
+        platform.save_item(HIGH_SCORES_KEY, "not json").unwrap();
+
+        assert_eq!(HighScores::load(&platform), HighScores::default());
+    }
+}
@@ -0,0 +1,18 @@
+use super::{game::Walk, RedHatBoy};
+use crate::engine::{NativePlatform, Platform, SpriteSheet};
+use futures::executor::block_on;
+use std::rc::Rc;
+
+pub(super) fn walk() -> Walk {
+    block_on(Walk::new(Rc::new(NativePlatform::default()), Some(1))).unwrap()
+}
+
+pub(super) fn boy() -> RedHatBoy {
+    let platform = NativePlatform::default();
+    let audio = platform.create_audio().unwrap();
+    block_on(RedHatBoy::load(&platform, audio)).unwrap()
+}
+
+pub(super) fn tiles() -> Rc<SpriteSheet> {
+    Rc::new(block_on(SpriteSheet::load(&NativePlatform::default(), "tiles")).unwrap())
+}
@@ -1,8 +1,10 @@
-use super::{browser, game::Walk};
-use crate::engine::{self, KeyState, Renderer};
+use super::{game::Walk, score_ui, Action, HighScores};
+use crate::engine::{KeyState, Recording, Renderer};
+use anyhow::Result;
 use futures::channel::mpsc::UnboundedReceiver;
 
 const TIMELINE_MINIMUM: i16 = 1000;
+pub(super) const PAUSE_UI: &str = "<div id='paused'>Paused</div>";
 
 pub(super) struct WalkTheDogState<S> {
     pub(super) _state: S,
@@ -10,7 +12,7 @@ pub(super) struct WalkTheDogState<S> {
 }
 
 impl<S> WalkTheDogState<S> {
-    pub(super) fn draw(&self, renderer: &Renderer) {
+    pub(super) fn draw(&self, renderer: &dyn Renderer) {
         self.walk.draw(renderer);
     }
 }
@@ -26,8 +28,14 @@ impl WalkTheDogState<Ready> {
     }
 
     pub(super) fn update(mut self, keystate: &KeyState) -> ReadyEndState {
+        self.walk.resume_after_hiding();
+        self.walk.record(keystate);
         self.walk.boy.update();
-        if keystate.is_pressed("ArrowRight") {
+        self.walk.dog.update(&self.walk.boy);
+        // A tap goes down and up within one tick, so it's never held.
+        if self.walk.is_active(Action::Run, keystate)
+            || self.walk.bindings.just_activated(Action::Run, keystate)
+        {
             ReadyEndState::Complete(self.start_running())
         } else {
             ReadyEndState::Continue(self)
@@ -56,57 +64,95 @@ pub(super) struct Walking;
 
 impl WalkTheDogState<Walking> {
     pub(super) fn update(mut self, keystate: &KeyState) -> WalkingEndState {
-        if keystate.is_pressed("Space") {
+        self.walk.record(keystate);
+
+        // A quick tap presses and releases within one tick, so both apply.
+        if self.walk.bindings.just_activated(Action::Jump, keystate) {
             self.walk.boy.jump();
         }
+        if self.walk.bindings.just_deactivated(Action::Jump, keystate) {
+            self.walk.boy.release_jump();
+        }
 
-        if keystate.is_pressed("ArrowDown") {
+        if self.walk.is_active(Action::Slide, keystate) {
             self.walk.boy.slide();
         }
 
         self.walk.boy.update();
+        let velocity = self.walk.velocity();
+        let advanced = self.walk.boy.advance(velocity);
+        self.walk.travel(advanced);
+        self.walk.dog.update(&self.walk.boy);
+        self.walk.move_camera();
 
-        let walking_speed = self.walk.velocity();
-        let [first_background, second_background] = &mut self.walk.backgrounds;
-        first_background.move_horizontally(walking_speed);
-        second_background.move_horizontally(walking_speed);
-
-        if first_background.right() < 0 {
-            first_background.set_x(second_background.right());
-        }
-        if second_background.right() < 0 {
-            second_background.set_x(first_background.right());
-        }
-
-        self.walk.obstacles.retain(|obstacle| obstacle.right() > 0);
+        self.walk.clear_obstacles();
 
+        let was_falling = self.walk.boy.falling();
         self.walk.obstacles.iter_mut().for_each(|obstacle| {
-            obstacle.move_horizontally(walking_speed);
+            obstacle.update();
             obstacle.check_intersection(&mut self.walk.boy);
         });
+        self.walk.check_footing();
+        self.walk.collect_items();
+        if !was_falling && self.walk.boy.falling() {
+            self.walk.shake_camera();
+        }
 
-        if self.walk.timeline < TIMELINE_MINIMUM {
+        if self.walk.timeline < self.walk.camera.visible().x() + TIMELINE_MINIMUM {
             self.walk.generate_next_segment();
-        } else {
-            self.walk.timeline += walking_speed;
         }
+        self.walk.recenter();
 
+        // Pausing only after the tick is simulated keeps every recorded tick
+        // a simulated one, so replays stay in step.
         if self.walk.knocked_out() {
             WalkingEndState::Complete(self.end_game())
+        } else if self.walk.pause_pressed(keystate) || self.walk.hidden() {
+            WalkingEndState::Paused(self.pause())
         } else {
             WalkingEndState::Continue(self)
         }
     }
 
+    fn pause(self) -> WalkTheDogState<Paused> {
+        if let Err(err) = self.walk.audio.suspend() {
+            error!("Could not suspend audio {:#?}", err);
+        }
+        if let Err(err) = self.walk.platform.draw_ui(PAUSE_UI) {
+            error!("Could not show the pause screen {:#?}", err);
+        }
+
+        WalkTheDogState {
+            _state: Paused,
+            walk: self.walk,
+        }
+    }
+
     fn end_game(self) -> WalkTheDogState<GameOver> {
-        let receiver = browser::draw_ui("<button id='new_game'>New Game</button>")
-            .and_then(|_| browser::find_html_element_by_id("new_game"))
-            .map(engine::add_click_handler)
-            .unwrap();
+        log!(
+            "Game over, replay this course with ?seed={}",
+            self.walk.seed()
+        );
+        let score = self.walk.score();
+        let mut high_scores = HighScores::load(self.walk.platform.as_ref());
+        let rank = high_scores.insert(score);
+        if let Err(err) = high_scores.save(self.walk.platform.as_ref()) {
+            error!("Could not save high scores {:#?}", err);
+        }
+
+        let mut summary = score_ui(score, &high_scores, rank);
+        match replay_link(self.walk.recording()) {
+            Ok(link) => summary.push_str(&link),
+            Err(err) => error!("Could not serialize replay {:#?}", err),
+        }
+        let (new_game_event, rebind_events) = show_game_over(&self.walk, &summary, None).unwrap();
 
         WalkTheDogState {
             _state: GameOver {
-                new_game_event: receiver,
+                new_game_event,
+                rebind_events,
+                rebinding: None,
+                summary,
             },
             walk: self.walk,
         }
@@ -115,30 +161,157 @@ impl WalkTheDogState<Walking> {
 
 pub(super) enum WalkingEndState {
     Complete(WalkTheDogState<GameOver>),
+    Paused(WalkTheDogState<Paused>),
     Continue(WalkTheDogState<Walking>),
 }
 
+pub(super) struct Paused;
+
+impl WalkTheDogState<Paused> {
+    pub(super) fn update(mut self, keystate: &KeyState) -> PausedEndState {
+        // Drained so coming back to the tab doesn't pause again.
+        self.walk.hidden();
+        if self.walk.pause_pressed(keystate) {
+            PausedEndState::Complete(self.resume())
+        } else {
+            PausedEndState::Continue(self)
+        }
+    }
+
+    fn resume(self) -> WalkTheDogState<Walking> {
+        if let Err(err) = self.walk.platform.hide_ui() {
+            error!("Could not hide the pause screen {:#?}", err);
+        }
+        if let Err(err) = self.walk.audio.resume() {
+            error!("Could not resume audio {:#?}", err);
+        }
+
+        WalkTheDogState {
+            _state: Walking,
+            walk: self.walk,
+        }
+    }
+}
+
+pub(super) enum PausedEndState {
+    Complete(WalkTheDogState<Walking>),
+    Continue(WalkTheDogState<Paused>),
+}
+
+type RebindEvents = Vec<(Action, UnboundedReceiver<()>)>;
+
+fn show_game_over(
+    walk: &Walk,
+    summary: &str,
+    rebinding: Option<Action>,
+) -> Result<(UnboundedReceiver<()>, RebindEvents)> {
+    walk.platform.draw_ui(&format!(
+        "<div id='game_over'>{}{}<button id='new_game'>New Game</button></div>",
+        summary,
+        walk.bindings.controls_ui(rebinding)
+    ))?;
+
+    let new_game_event = walk.platform.add_click_handler("new_game")?;
+    let rebind_events = Action::ALL
+        .iter()
+        .map(|&action| {
+            Ok((
+                action,
+                walk.platform.add_click_handler(&action.button_id())?,
+            ))
+        })
+        .collect::<Result<_>>()?;
+    Ok((new_game_event, rebind_events))
+}
+
+/// The saved file plays back with `?replay=replay.json`.
+fn replay_link(recording: &Recording) -> Result<String> {
+    let href: String = serde_json::to_string(recording)?
+        .bytes()
+        .map(|byte| match byte {
+            b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z' | b'{' | b'}' | b'[' | b']' | b':' | b',' => {
+                char::from(byte).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+    Ok(format!(
+        "<a id='replay' download='replay.json' href='data:application/json,{}'>Download replay</a>",
+        href
+    ))
+}
+
 pub(super) struct GameOver {
     pub(super) new_game_event: UnboundedReceiver<()>,
+    pub(super) rebind_events: RebindEvents,
+    pub(super) rebinding: Option<Action>,
+    pub(super) summary: String,
 }
 
 impl GameOver {
     fn new_game_pressed(&mut self) -> bool {
         matches!(self.new_game_event.try_next(), Ok(Some(())))
     }
+
+    fn rebind_pressed(&mut self) -> Option<Action> {
+        self.rebind_events
+            .iter_mut()
+            .find_map(|(action, event)| match event.try_next() {
+                Ok(Some(())) => Some(*action),
+                _ => None,
+            })
+    }
+
+    /// Keys already held when the rebind button was clicked don't count.
+    fn new_key(&self, keystate: &KeyState) -> Option<(Action, String)> {
+        let action = self.rebinding?;
+        let code = keystate
+            .pressed_keys()
+            .filter(|code| keystate.just_pressed(code))
+            .min()?;
+        Some((action, code.to_string()))
+    }
 }
 
 impl WalkTheDogState<GameOver> {
-    pub(super) fn update(mut self) -> GameOverEndState {
+    pub(super) fn update(mut self, keystate: &KeyState) -> GameOverEndState {
+        self.walk.resume_after_hiding();
         if self._state.new_game_pressed() {
-            GameOverEndState::Complete(self.new_game())
-        } else {
-            GameOverEndState::Continue(self)
+            return GameOverEndState::Complete(self.new_game());
+        }
+
+        if let Some(action) = self._state.rebind_pressed() {
+            self._state.rebinding = Some(action);
+            self.redraw_ui();
+        } else if let Some((action, code)) = self._state.new_key(keystate) {
+            self.walk.bindings.rebind(action, &code);
+            if let Err(err) = self.walk.bindings.save(self.walk.platform.as_ref()) {
+                error!("Could not save key bindings {:#?}", err);
+            }
+            self._state.rebinding = None;
+            self.redraw_ui();
+        }
+        GameOverEndState::Continue(self)
+    }
+
+    fn redraw_ui(&mut self) {
+        let rebinding = self._state.rebinding;
+        let shown = self
+            .walk
+            .platform
+            .hide_ui()
+            .and_then(|_| show_game_over(&self.walk, &self._state.summary, rebinding));
+        match shown {
+            Ok((new_game_event, rebind_events)) => {
+                self._state.new_game_event = new_game_event;
+                self._state.rebind_events = rebind_events;
+            }
+            Err(err) => error!("Could not redraw the game over screen {:#?}", err),
         }
     }
 
     pub(super) fn new_game(self) -> WalkTheDogState<Ready> {
-        if let Err(err) = browser::hide_ui() {
+        if let Err(err) = self.walk.platform.hide_ui() {
             error!("Error hiding the browser {:#?}", err);
         }
 
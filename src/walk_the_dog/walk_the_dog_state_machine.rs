@@ -4,6 +4,7 @@ use crate::engine::{KeyState, Renderer};
 pub(super) enum WalkTheDogStateMachine {
     Ready(WalkTheDogState<Ready>),
     Walking(WalkTheDogState<Walking>),
+    Paused(WalkTheDogState<Paused>),
     GameOver(WalkTheDogState<GameOver>),
 }
 
@@ -16,14 +17,16 @@ impl WalkTheDogStateMachine {
         match self {
             WalkTheDogStateMachine::Ready(state) => state.update(keystate).into(),
             WalkTheDogStateMachine::Walking(state) => state.update(keystate).into(),
-            WalkTheDogStateMachine::GameOver(state) => state.update().into(),
+            WalkTheDogStateMachine::Paused(state) => state.update(keystate).into(),
+            WalkTheDogStateMachine::GameOver(state) => state.update(keystate).into(),
         }
     }
 
-    pub(super) fn draw(&self, renderer: &Renderer) {
+    pub(super) fn draw(&self, renderer: &dyn Renderer) {
         match self {
             WalkTheDogStateMachine::Ready(state) => state.draw(renderer),
             WalkTheDogStateMachine::Walking(state) => state.draw(renderer),
+            WalkTheDogStateMachine::Paused(state) => state.draw(renderer),
             WalkTheDogStateMachine::GameOver(state) => state.draw(renderer),
         }
     }
@@ -41,6 +44,12 @@ impl From<WalkTheDogState<Walking>> for WalkTheDogStateMachine {
     }
 }
 
+impl From<WalkTheDogState<Paused>> for WalkTheDogStateMachine {
+    fn from(state: WalkTheDogState<Paused>) -> Self {
+        WalkTheDogStateMachine::Paused(state)
+    }
+}
+
 impl From<WalkTheDogState<GameOver>> for WalkTheDogStateMachine {
     fn from(state: WalkTheDogState<GameOver>) -> Self {
         WalkTheDogStateMachine::GameOver(state)
@@ -60,11 +69,21 @@ impl From<WalkingEndState> for WalkTheDogStateMachine {
     fn from(state: WalkingEndState) -> Self {
         match state {
             WalkingEndState::Complete(game_over) => WalkTheDogStateMachine::GameOver(game_over),
+            WalkingEndState::Paused(paused) => WalkTheDogStateMachine::Paused(paused),
             WalkingEndState::Continue(walking) => WalkTheDogStateMachine::Walking(walking),
         }
     }
 }
 
+impl From<PausedEndState> for WalkTheDogStateMachine {
+    fn from(state: PausedEndState) -> Self {
+        match state {
+            PausedEndState::Complete(walking) => WalkTheDogStateMachine::Walking(walking),
+            PausedEndState::Continue(paused) => WalkTheDogStateMachine::Paused(paused),
+        }
+    }
+}
+
 impl From<GameOverEndState> for WalkTheDogStateMachine {
     fn from(state: GameOverEndState) -> Self {
         match state {
@@ -73,3 +92,285 @@ impl From<GameOverEndState> for WalkTheDogStateMachine {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{NativePlatform, Recording, Replay, TOUCH_TAP};
+    use crate::walk_the_dog::{test_fixtures::walk, Action, Bindings, RELEASED_JUMP_SPEED};
+    use futures::executor::block_on;
+    use std::rc::Rc;
+
+    const MAX_TICKS: usize = 10_000;
+
+    fn play(seed: u64, mut input: impl FnMut(usize, &mut KeyState)) -> (usize, Recording) {
+        let walk = block_on(Walk::new(Rc::new(NativePlatform::default()), Some(seed))).unwrap();
+        let mut machine = WalkTheDogStateMachine::new(walk);
+        let mut keystate = KeyState::new();
+
+        for tick in 0..MAX_TICKS {
+            input(tick, &mut keystate);
+            machine = machine.update(&keystate);
+            if let WalkTheDogStateMachine::GameOver(state) = &machine {
+                return (tick, state.walk.recording().clone());
+            }
+        }
+        panic!("the run did not end within {} ticks", MAX_TICKS);
+    }
+
+    #[test]
+    fn replaying_a_recording_reproduces_the_run() {
+        let (ticks, recording) = play(1234, |tick, keys| {
+            if tick % 45 == 30 {
+                tap(keys, &["ArrowRight"], "Space");
+            } else {
+                hold(keys, &["ArrowRight"]);
+            }
+        });
+
+        let json = serde_json::to_string(&recording).unwrap();
+        let mut replay = Replay::new(serde_json::from_str(&json).unwrap());
+        let (replayed_ticks, replayed_recording) = play(recording.seed, |_, keys| {
+            assert!(
+                replay.next_tick(keys),
+                "replay ran out before the game ended"
+            );
+        });
+
+        assert_eq!(replayed_ticks, ticks);
+        assert_eq!(replayed_recording, recording);
+    }
+
+    fn hold<'a>(keystate: &'a mut KeyState, codes: &[&str]) -> &'a KeyState {
+        keystate.update_pressed(codes.iter().copied());
+        keystate
+    }
+
+    #[test]
+    fn pause_key_freezes_the_walk_until_pressed_again() {
+        let platform = Rc::new(NativePlatform::default());
+        let walk = block_on(Walk::new(platform.clone(), Some(1))).unwrap();
+        let mut keys = KeyState::new();
+        let mut machine = WalkTheDogStateMachine::new(walk)
+            .update(hold(&mut keys, &["ArrowRight"]))
+            .update(hold(&mut keys, &["ArrowRight"]));
+        assert!(matches!(machine, WalkTheDogStateMachine::Walking(_)));
+
+        machine = machine.update(hold(&mut keys, &["ArrowRight", "KeyP"]));
+        let WalkTheDogStateMachine::Paused(state) = &machine else {
+            panic!("the pause key should pause");
+        };
+        let distance = state.walk.distance;
+        assert_eq!(platform.ui(), vec![PAUSE_UI.to_string()]);
+        assert!(platform.audio().is_suspended());
+
+        for codes in [&["KeyP"][..], &[], &["ArrowRight"]] {
+            machine = machine.update(hold(&mut keys, codes));
+            assert!(matches!(machine, WalkTheDogStateMachine::Paused(_)));
+        }
+
+        machine = machine.update(hold(&mut keys, &["Escape"]));
+        let WalkTheDogStateMachine::Walking(state) = &machine else {
+            panic!("pressing the pause key again should resume");
+        };
+        assert_eq!(state.walk.distance, distance);
+        assert!(platform.ui().is_empty());
+        assert!(!platform.audio().is_suspended());
+    }
+
+    #[test]
+    fn a_tap_starts_the_run() {
+        let walk = walk();
+        let mut keystate = KeyState::new();
+        keystate.start_tick();
+        keystate.set_pressed(TOUCH_TAP);
+        keystate.set_released(TOUCH_TAP);
+
+        let machine = WalkTheDogStateMachine::new(walk).update(&keystate);
+
+        assert!(matches!(machine, WalkTheDogStateMachine::Walking(_)));
+    }
+
+    fn walking(platform: Rc<NativePlatform>, keys: &mut KeyState) -> WalkTheDogStateMachine {
+        let walk = block_on(Walk::new(platform, Some(1))).unwrap();
+        let machine = WalkTheDogStateMachine::new(walk).update(hold(keys, &["ArrowRight"]));
+        assert!(matches!(machine, WalkTheDogStateMachine::Walking(_)));
+        machine
+    }
+
+    fn tap<'a>(keystate: &'a mut KeyState, held: &[&str], code: &str) -> &'a KeyState {
+        keystate.update_pressed(held.iter().copied());
+        keystate.set_pressed(code);
+        keystate.set_released(code);
+        keystate
+    }
+
+    #[test]
+    fn a_jump_tapped_within_one_tick_is_also_released() {
+        let mut keys = KeyState::new();
+        let machine = walking(Rc::new(NativePlatform::default()), &mut keys);
+
+        let machine = machine.update(tap(&mut keys, &["ArrowRight"], "Space"));
+        let WalkTheDogStateMachine::Walking(state) = &machine else {
+            panic!("the boy should still be walking");
+        };
+        assert!(state.walk.boy.jumping());
+        assert!(f32::from(state.walk.boy.velocity_y()) >= RELEASED_JUMP_SPEED);
+    }
+
+    /// Taps if `ticks_held` is 0.
+    fn peak_of_jump(ticks_held: usize) -> i16 {
+        let mut keys = KeyState::new();
+        let mut machine = walking(Rc::new(NativePlatform::default()), &mut keys);
+        machine = if ticks_held == 0 {
+            machine.update(tap(&mut keys, &["ArrowRight"], "Space"))
+        } else {
+            machine.update(hold(&mut keys, &["ArrowRight", "Space"]))
+        };
+
+        let mut peak = i16::MAX;
+        for tick in 1.. {
+            let WalkTheDogStateMachine::Walking(state) = &machine else {
+                panic!("the boy should land his jump");
+            };
+            if !state.walk.boy.jumping() {
+                break;
+            }
+            peak = peak.min(state.walk.boy.pos_y());
+            let codes: &[&str] = if tick < ticks_held {
+                &["ArrowRight", "Space"]
+            } else {
+                &["ArrowRight"]
+            };
+            machine = machine.update(hold(&mut keys, codes));
+        }
+        peak
+    }
+
+    #[test]
+    fn a_tapped_jump_is_a_short_hop() {
+        assert!(peak_of_jump(0) > peak_of_jump(30));
+        assert!(peak_of_jump(0) >= peak_of_jump(1));
+    }
+
+    #[test]
+    fn hiding_the_page_pauses_the_walk() {
+        let platform = Rc::new(NativePlatform::default());
+        let walk = block_on(Walk::new(platform.clone(), Some(1))).unwrap();
+        let mut keys = KeyState::new();
+        let mut machine =
+            WalkTheDogStateMachine::new(walk).update(hold(&mut keys, &["ArrowRight"]));
+
+        platform.set_hidden(true).unwrap();
+        machine = machine.update(hold(&mut keys, &["ArrowRight"]));
+        assert!(matches!(machine, WalkTheDogStateMachine::Paused(_)));
+
+        platform.set_hidden(false).unwrap();
+        machine = machine.update(hold(&mut keys, &[]));
+        assert!(matches!(machine, WalkTheDogStateMachine::Paused(_)));
+
+        machine = machine.update(hold(&mut keys, &["KeyP"]));
+        assert!(matches!(machine, WalkTheDogStateMachine::Walking(_)));
+    }
+
+    #[test]
+    fn a_page_hidden_and_shown_between_ticks_still_pauses() {
+        let platform = Rc::new(NativePlatform::default());
+        let walk = block_on(Walk::new(platform.clone(), Some(1))).unwrap();
+        let mut keys = KeyState::new();
+        let mut machine =
+            WalkTheDogStateMachine::new(walk).update(hold(&mut keys, &["ArrowRight"]));
+
+        platform.set_hidden(true).unwrap();
+        assert!(platform.audio().is_suspended());
+        platform.set_hidden(false).unwrap();
+        machine = machine.update(hold(&mut keys, &["ArrowRight"]));
+
+        assert!(matches!(machine, WalkTheDogStateMachine::Paused(_)));
+        assert!(platform.audio().is_suspended());
+    }
+
+    #[test]
+    fn hiding_the_page_before_the_run_starts_only_interrupts_the_audio() {
+        let platform = Rc::new(NativePlatform::default());
+        let walk = block_on(Walk::new(platform.clone(), Some(1))).unwrap();
+        let mut keys = KeyState::new();
+
+        platform.set_hidden(true).unwrap();
+        platform.set_hidden(false).unwrap();
+        let machine = WalkTheDogStateMachine::new(walk).update(hold(&mut keys, &[]));
+
+        assert!(matches!(machine, WalkTheDogStateMachine::Ready(_)));
+        assert!(!platform.audio().is_suspended());
+    }
+
+    #[test]
+    fn a_recording_made_across_a_pause_replays_in_step() {
+        let (ticks, recording) = play(1234, |tick, keys| {
+            match tick {
+                10 | 25 => hold(keys, &["ArrowRight", "KeyP"]),
+                _ if tick % 45 == 30 => tap(keys, &["ArrowRight"], "Space"),
+                _ => hold(keys, &["ArrowRight"]),
+            };
+        });
+        assert!(ticks > 25, "the run should outlast the pause");
+
+        let platform = Rc::new(NativePlatform::default());
+        let walk = block_on(Walk::new(platform.clone(), Some(recording.seed))).unwrap();
+        let mut machine = WalkTheDogStateMachine::new(walk);
+        let mut replay = Replay::new(recording.clone());
+        let mut keystate = KeyState::new();
+        for tick in 0..MAX_TICKS {
+            if tick == 40 {
+                platform.set_hidden(true).unwrap();
+                platform.set_hidden(false).unwrap();
+            }
+            if matches!(machine, WalkTheDogStateMachine::Paused(_)) {
+                keystate.start_tick();
+                if tick == 60 {
+                    keystate.set_pressed("KeyP");
+                    keystate.set_released("KeyP");
+                }
+            } else {
+                assert!(
+                    replay.next_tick(&mut keystate),
+                    "replay ran out before the game ended"
+                );
+            }
+            machine = machine.update(&keystate);
+            if let WalkTheDogStateMachine::GameOver(state) = &machine {
+                assert!(tick > 60, "the replay should outlast its pause");
+                assert_eq!(state.walk.recording(), &recording);
+                return;
+            }
+        }
+        panic!("the replay did not end within {} ticks", MAX_TICKS);
+    }
+
+    #[test]
+    fn game_over_screen_rebinds_an_action_to_the_next_new_key() {
+        let platform = Rc::new(NativePlatform::default());
+        let walk = block_on(Walk::new(platform.clone(), Some(1))).unwrap();
+        let mut machine = WalkTheDogStateMachine::new(walk);
+        let mut keys = KeyState::new();
+        while !matches!(machine, WalkTheDogStateMachine::GameOver(_)) {
+            machine = machine.update(hold(&mut keys, &["ArrowRight"]));
+        }
+
+        platform.click(&Action::Jump.button_id()).unwrap();
+        machine = machine.update(hold(&mut keys, &["ArrowRight"]));
+        assert!(platform.ui()[0].contains("Jump: press a key"));
+
+        machine = machine.update(hold(&mut keys, &["ArrowRight", "KeyW"]));
+        let WalkTheDogStateMachine::GameOver(state) = &machine else {
+            panic!("rebinding should stay on the game over screen");
+        };
+        assert_eq!(state.walk.bindings.codes(Action::Jump)[0], "KeyW");
+        assert!(platform.ui()[0].contains("Jump: KeyW"));
+        assert_eq!(platform.ui().len(), 1);
+        assert_eq!(
+            Bindings::load(platform.as_ref()).codes(Action::Jump)[0],
+            "KeyW"
+        );
+    }
+}